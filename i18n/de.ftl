@@ -0,0 +1,107 @@
+summary-nothing = Alles gut
+summary-restart-session = Starte deine Sitzung neu btw
+summary-reboot = Starte Arch neu btw
+
+body-nothing = Nichts Relevantes wurde aktualisiert
+body-restart-session = Systempakete wurden aktualisiert. Du solltest dich abmelden, um deine Sitzung neu zu starten.
+body-reboot = Systempakete wurden aktualisiert. Du solltest dein System neu starten!
+body-kernel-update = Der Kernel wurde aktualisiert. Du solltest dein System neu starten!
+
+details-kernel = Kernel { $running } läuft, { $installed } ist installiert ({ $ago })
+
+verbose-kernel = Kernel
+verbose-kernel-installed = installiert: { $version } ({ $ago })
+verbose-kernel-running = läuft:       { $version }
+
+action-reboot-now = Jetzt neu starten
+action-log-out = Abmelden
+action-remind-later = Später erinnern
+
+acknowledged = Bestätigt: { $summary }
+snoozed = Zurückgestellt: { $summary }
+suppressed = { $summary } (bestätigt oder zurückgestellt)
+
+status-transaction-in-progress = pacman läuft
+transaction-in-progress = Eine pacman-Transaktion läuft gerade, prüfe erneut sobald sie abgeschlossen ist
+
+pre-hook-kernel = Der laufende Kernel { $package } wird aktualisiert: Kernelmodule können bis zum Neustart nicht mehr geladen werden
+pre-hook-modules = { $package } ersetzt Module des laufenden Kernels: sie können bis zum Neustart nicht mehr geladen werden
+pre-hook-abort = Transaktion wird abgebrochen, da { $package } geschützt ist
+
+preview = Nach dem Upgrade: { $summary }
+
+install-installed = { $path } installiert
+install-removed = { $path } entfernt
+install-up-to-date = { $path } ist aktuell
+install-outdated = { $path } weicht von der generierten Datei ab
+install-missing = { $path } fehlt
+install-enable = Aktiviere es mit: { $command }
+install-disable = Wer es aktiviert hat, muss es deaktivieren mit: { $command }
+install-not-managed = { $path } wurde nicht von reboot-arch-btw generiert und bleibt unverändert
+
+history-resolved = { $start }: { $result ->
+    [restart-session] Sitzungsneustart
+    [reboot] Neustart
+   *[kernel-update] Neustart für Kernel-Update
+} für { $duration } erforderlich ({ $packages })
+history-pending = { $start }: { $result ->
+    [restart-session] Sitzungsneustart
+    [reboot] Neustart
+   *[kernel-update] Neustart für Kernel-Update
+} erforderlich, bisher { $duration } ({ $packages })
+history-kernel-updates = { $count ->
+    [one] { $count } Kernel-Update
+   *[other] { $count } Kernel-Updates
+}, ohne Neustart für durchschnittlich { $average } und höchstens { $max }
+history-empty = Bisher war kein Neustart erforderlich
+
+explain-since-session = Seit Beginn der Sitzung installiert:
+explain-before-session = Seit dem Start installiert, vor Beginn der Sitzung:
+explain-nothing = Seit dem Start wurde nichts installiert
+explain-result = { $result ->
+    [kernel-update] Kernel-Update
+    [reboot] Neustart
+    [restart-session] Sitzungsneustart
+   *[nothing] in keiner Liste
+}
+explain-relevance = { $relevance ->
+    [library] enthält Bibliotheken
+    [service] enthält Systemdienste
+   *[library-and-service] enthält Bibliotheken und Systemdienste
+}
+
+unknown = unbekannt
+
+duration-seconds = { $count ->
+    [one] { $count } Sekunde
+   *[other] { $count } Sekunden
+}
+duration-minutes = { $count ->
+    [one] { $count } Minute
+   *[other] { $count } Minuten
+}
+duration-hours = { $count ->
+    [one] { $count } Stunde
+   *[other] { $count } Stunden
+}
+duration-days = { $count ->
+    [one] { $count } Tag
+   *[other] { $count } Tage
+}
+
+ago-seconds = { $count ->
+    [one] vor { $count } Sekunde
+   *[other] vor { $count } Sekunden
+}
+ago-minutes = { $count ->
+    [one] vor { $count } Minute
+   *[other] vor { $count } Minuten
+}
+ago-hours = { $count ->
+    [one] vor { $count } Stunde
+   *[other] vor { $count } Stunden
+}
+ago-days = { $count ->
+    [one] vor { $count } Tag
+   *[other] vor { $count } Tagen
+}
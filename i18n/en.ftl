@@ -0,0 +1,107 @@
+summary-nothing = All good
+summary-restart-session = Restart your session btw
+summary-reboot = Reboot arch btw
+
+body-nothing = Nothing relevant got updated
+body-restart-session = System packages got updated. You should logout to restart your session.
+body-reboot = System packages got updated. You should reboot your system!
+body-kernel-update = Kernel got updated. You should reboot your system!
+
+details-kernel = Kernel { $running } running, { $installed } installed ({ $ago })
+
+verbose-kernel = Kernel
+verbose-kernel-installed = installed: { $version } (since { $ago })
+verbose-kernel-running = running:   { $version }
+
+action-reboot-now = Reboot now
+action-log-out = Log out
+action-remind-later = Remind me later
+
+acknowledged = Acknowledged: { $summary }
+snoozed = Snoozed: { $summary }
+suppressed = { $summary } (acknowledged or snoozed)
+
+status-transaction-in-progress = pacman running
+transaction-in-progress = A pacman transaction is in progress, check again once it finished
+
+pre-hook-kernel = Upgrading the running kernel { $package }: loading kernel modules will fail until you reboot
+pre-hook-modules = Upgrading { $package } replaces modules of the running kernel: loading them will fail until you reboot
+pre-hook-abort = Aborting the transaction since { $package } is protected
+
+preview = After upgrading: { $summary }
+
+install-installed = Installed { $path }
+install-removed = Removed { $path }
+install-up-to-date = { $path } is up to date
+install-outdated = { $path } differs from the generated file
+install-missing = { $path } is missing
+install-enable = Enable it with: { $command }
+install-disable = Everyone who enabled it has to disable it with: { $command }
+install-not-managed = { $path } was not generated by reboot-arch-btw, leaving it alone
+
+history-resolved = { $start }: { $result ->
+    [restart-session] session restart
+    [reboot] reboot
+   *[kernel-update] reboot for kernel update
+} required for { $duration } ({ $packages })
+history-pending = { $start }: { $result ->
+    [restart-session] session restart
+    [reboot] reboot
+   *[kernel-update] reboot for kernel update
+} required for { $duration } so far ({ $packages })
+history-kernel-updates = { $count ->
+    [one] { $count } kernel update
+   *[other] { $count } kernel updates
+}, unrebooted for { $average } on average and { $max } at most
+history-empty = No reboot or session restart was required so far
+
+explain-since-session = Installed since the session started:
+explain-before-session = Installed since boot, before the session started:
+explain-nothing = Nothing got installed since boot
+explain-result = { $result ->
+    [kernel-update] kernel update
+    [reboot] reboot
+    [restart-session] session restart
+   *[nothing] not listed
+}
+explain-relevance = { $relevance ->
+    [library] ships libraries
+    [service] ships system services
+   *[library-and-service] ships libraries and system services
+}
+
+unknown = unknown
+
+duration-seconds = { $count ->
+    [one] { $count } second
+   *[other] { $count } seconds
+}
+duration-minutes = { $count ->
+    [one] { $count } minute
+   *[other] { $count } minutes
+}
+duration-hours = { $count ->
+    [one] { $count } hour
+   *[other] { $count } hours
+}
+duration-days = { $count ->
+    [one] { $count } day
+   *[other] { $count } days
+}
+
+ago-seconds = { $count ->
+    [one] { $count } second ago
+   *[other] { $count } seconds ago
+}
+ago-minutes = { $count ->
+    [one] { $count } minute ago
+   *[other] { $count } minutes ago
+}
+ago-hours = { $count ->
+    [one] { $count } hour ago
+   *[other] { $count } hours ago
+}
+ago-days = { $count ->
+    [one] { $count } day ago
+   *[other] { $count } days ago
+}
@@ -0,0 +1,107 @@
+summary-nothing = Tout va bien
+summary-restart-session = Redémarre ta session btw
+summary-reboot = Redémarre Arch btw
+
+body-nothing = Rien d'important n'a été mis à jour
+body-restart-session = Des paquets système ont été mis à jour. Tu devrais te déconnecter pour redémarrer ta session.
+body-reboot = Des paquets système ont été mis à jour. Tu devrais redémarrer ton système !
+body-kernel-update = Le noyau a été mis à jour. Tu devrais redémarrer ton système !
+
+details-kernel = Noyau { $running } en cours d'exécution, { $installed } installé ({ $ago })
+
+verbose-kernel = Noyau
+verbose-kernel-installed = installé :  { $version } ({ $ago })
+verbose-kernel-running = en cours : { $version }
+
+action-reboot-now = Redémarrer maintenant
+action-log-out = Se déconnecter
+action-remind-later = Me le rappeler plus tard
+
+acknowledged = Confirmé : { $summary }
+snoozed = Reporté : { $summary }
+suppressed = { $summary } (confirmé ou reporté)
+
+status-transaction-in-progress = pacman en cours
+transaction-in-progress = Une transaction pacman est en cours, vérifiez à nouveau une fois terminée
+
+pre-hook-kernel = Mise à jour du noyau en cours d’exécution { $package } : le chargement des modules échouera jusqu’au redémarrage
+pre-hook-modules = { $package } remplace des modules du noyau en cours d’exécution : leur chargement échouera jusqu’au redémarrage
+pre-hook-abort = Abandon de la transaction car { $package } est protégé
+
+preview = Après la mise à jour : { $summary }
+
+install-installed = { $path } installé
+install-removed = { $path } supprimé
+install-up-to-date = { $path } est à jour
+install-outdated = { $path } diffère du fichier généré
+install-missing = { $path } est manquant
+install-enable = Activez-le avec : { $command }
+install-disable = Quiconque l'a activé doit le désactiver avec : { $command }
+install-not-managed = { $path } n'a pas été généré par reboot-arch-btw, il reste inchangé
+
+history-resolved = { $start } : { $result ->
+    [restart-session] redémarrage de session
+    [reboot] redémarrage
+   *[kernel-update] redémarrage pour mise à jour du noyau
+} requis pendant { $duration } ({ $packages })
+history-pending = { $start } : { $result ->
+    [restart-session] redémarrage de session
+    [reboot] redémarrage
+   *[kernel-update] redémarrage pour mise à jour du noyau
+} requis depuis { $duration } ({ $packages })
+history-kernel-updates = { $count ->
+    [one] { $count } mise à jour du noyau
+   *[other] { $count } mises à jour du noyau
+}, sans redémarrage pendant { $average } en moyenne et { $max } au plus
+history-empty = Aucun redémarrage n’a été requis jusqu’à présent
+
+explain-since-session = Installés depuis le début de la session :
+explain-before-session = Installés depuis le démarrage, avant le début de la session :
+explain-nothing = Rien n’a été installé depuis le démarrage
+explain-result = { $result ->
+    [kernel-update] mise à jour du noyau
+    [reboot] redémarrage
+    [restart-session] redémarrage de session
+   *[nothing] dans aucune liste
+}
+explain-relevance = { $relevance ->
+    [library] contient des bibliothèques
+    [service] contient des services système
+   *[library-and-service] contient des bibliothèques et des services système
+}
+
+unknown = inconnu
+
+duration-seconds = { $count ->
+    [one] { $count } seconde
+   *[other] { $count } secondes
+}
+duration-minutes = { $count ->
+    [one] { $count } minute
+   *[other] { $count } minutes
+}
+duration-hours = { $count ->
+    [one] { $count } heure
+   *[other] { $count } heures
+}
+duration-days = { $count ->
+    [one] { $count } jour
+   *[other] { $count } jours
+}
+
+ago-seconds = { $count ->
+    [one] il y a { $count } seconde
+   *[other] il y a { $count } secondes
+}
+ago-minutes = { $count ->
+    [one] il y a { $count } minute
+   *[other] il y a { $count } minutes
+}
+ago-hours = { $count ->
+    [one] il y a { $count } heure
+   *[other] il y a { $count } heures
+}
+ago-days = { $count ->
+    [one] il y a { $count } jour
+   *[other] il y a { $count } jours
+}
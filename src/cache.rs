@@ -0,0 +1,128 @@
+//! Cache of the last report for `status --cached`.
+//!
+//! Opening the pacman database and parsing utmp is too slow for shell prompts, so the last report
+//! gets reused as long as nothing it depends on changed.
+
+use crate::checks::CheckReport;
+use crate::config::xdg_dir;
+use crate::session;
+use crate::watch::PACMAN_LOCAL_DB_PATH;
+use anyhow::{Context, Result, anyhow};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const UTMP_PATH: &str = "/run/utmp";
+
+/// Everything a cached report depends on.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// Changes with every reboot.
+    pub boot_id: String,
+    /// Changes when packages get installed, upgraded or removed.
+    pub local_db_mtime: SystemTime,
+    /// Changes when someone logs in or out.
+    pub utmp_mtime: Option<SystemTime>,
+    pub reboot_packages: Vec<String>,
+    pub session_restart_packages: Vec<String>,
+    pub session_dependency_packages: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Cache {
+    pub key: CacheKey,
+    pub report: CheckReport,
+}
+
+impl CacheKey {
+    pub fn current(
+        reboot_packages: &[String],
+        session_restart_packages: &[String],
+        session_dependency_packages: &[String],
+    ) -> Result<CacheKey> {
+        Ok(CacheKey {
+            boot_id: session::boot_id()?,
+            local_db_mtime: mtime(Path::new(PACMAN_LOCAL_DB_PATH))?,
+            utmp_mtime: mtime(Path::new(UTMP_PATH)).ok(),
+            reboot_packages: reboot_packages.to_vec(),
+            session_restart_packages: session_restart_packages.to_vec(),
+            session_dependency_packages: session_dependency_packages.to_vec(),
+        })
+    }
+}
+
+fn mtime(path: &Path) -> Result<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| anyhow!("Could not get modification time of {}", path.display()))
+}
+
+/// Path of the cache file, usually `~/.cache/reboot-arch-btw/status.json`.
+pub fn path() -> Result<PathBuf> {
+    Ok(xdg_dir("XDG_CACHE_HOME", ".cache")?.join("reboot-arch-btw/status.json"))
+}
+
+/// The cached report and the key it was stored with.
+pub fn load(path: &Path) -> Option<Cache> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// The cached report, if it was stored with `key`.
+pub fn load_valid(path: &Path, key: &CacheKey) -> Option<CheckReport> {
+    let cache = load(path)?;
+    if cache.key == *key {
+        Some(cache.report)
+    } else {
+        info!("Cache is outdated");
+        None
+    }
+}
+
+pub fn store(path: &Path, key: CacheKey, report: &CheckReport) -> Result<()> {
+    let dir = path.parent().expect("cache file has a parent directory");
+    fs::create_dir_all(dir).with_context(|| anyhow!("Could not create {}", dir.display()))?;
+    let cache = Cache {
+        key,
+        report: report.clone(),
+    };
+    fs::write(path, serde_json::to_string(&cache)?)
+        .with_context(|| anyhow!("Could not write {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::CheckResult;
+    use crate::test_dir::TestDir;
+    use std::time::Duration;
+
+    fn key(boot_id: &str) -> CacheKey {
+        CacheKey {
+            boot_id: boot_id.to_owned(),
+            local_db_mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(1000),
+            utmp_mtime: None,
+            reboot_packages: vec!["systemd".to_owned()],
+            session_restart_packages: vec![],
+            session_dependency_packages: vec![],
+        }
+    }
+
+    #[test]
+    fn test_store_load() {
+        let dir = TestDir::new("cache");
+        let path = dir.path().join("cache/status.json");
+        let report = CheckReport {
+            result: CheckResult::Reboot,
+            ..CheckReport::default()
+        };
+        assert_eq!(load_valid(&path, &key("a")), None);
+        store(&path, key("a"), &report).unwrap();
+        assert_eq!(load_valid(&path, &key("a")), Some(report.clone()));
+        // A reboot invalidates the cache, but the outdated report can still be loaded.
+        assert_eq!(load_valid(&path, &key("b")), None);
+        assert_eq!(load(&path).map(|cache| cache.report), Some(report));
+    }
+}
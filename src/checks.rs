@@ -1,5 +1,12 @@
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+use crate::i18n::{tr, tr_args};
+use crate::package::PackageInfo;
+use crate::pacman_log::PacmanLog;
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum CheckResult {
+    #[default]
     Nothing,
     RestartSession,
     Reboot,
@@ -7,28 +14,167 @@ pub enum CheckResult {
 }
 
 impl CheckResult {
-    pub fn summary(&self) -> &'static str {
+    /// The kebab case name, e.g. "kernel-update", the same as in the JSON of the history.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CheckResult::Nothing => "nothing",
+            CheckResult::RestartSession => "restart-session",
+            CheckResult::Reboot => "reboot",
+            CheckResult::KernelUpdate => "kernel-update",
+        }
+    }
+
+    pub fn summary(&self) -> String {
         match self {
-            CheckResult::Nothing => "All good",
-            CheckResult::RestartSession => "Restart your session btw",
-            CheckResult::Reboot | CheckResult::KernelUpdate => "Reboot arch btw",
+            CheckResult::Nothing => tr("summary-nothing"),
+            CheckResult::RestartSession => tr("summary-restart-session"),
+            CheckResult::Reboot | CheckResult::KernelUpdate => tr("summary-reboot"),
         }
     }
 
-    pub fn body(&self) -> &'static str {
+    pub fn body(&self) -> String {
         match self {
-            CheckResult::Nothing => "Nothing relevant got updated",
-            CheckResult::RestartSession => {
-                "System packages got updated. You should logout to restart your session."
-            }
-            CheckResult::Reboot => "System packages got updated. You should reboot your system!",
-            CheckResult::KernelUpdate => "Kernel got updated. You should reboot your system!",
+            CheckResult::Nothing => tr("body-nothing"),
+            CheckResult::RestartSession => tr("body-restart-session"),
+            CheckResult::Reboot => tr("body-reboot"),
+            CheckResult::KernelUpdate => tr("body-kernel-update"),
+        }
+    }
+}
+
+/// A package whose update made a check report something.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct TriggeringPackage {
+    pub name: String,
+    #[serde(flatten)]
+    pub info: PackageInfo,
+    /// The version which got replaced by the update, if known.
+    pub previous_version: Option<String>,
+    /// What the update of this package requires.
+    pub result: CheckResult,
+}
+
+impl TriggeringPackage {
+    /// Describe the update, e.g. "systemd 256.4-1 → 256.5-1 (2 hours ago)".
+    pub fn describe(&self) -> String {
+        match &self.previous_version {
+            Some(previous_version) => format!(
+                "{} {previous_version} → {} ({})",
+                self.name,
+                self.info.version,
+                self.info.installed_reltime()
+            ),
+            None => format!(
+                "{} {} ({})",
+                self.name,
+                self.info.version,
+                self.info.installed_reltime()
+            ),
         }
     }
 }
 
+/// The running and the installed kernel.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct KernelVersions {
+    pub running: String,
+    pub installed: PackageInfo,
+}
+
+/// The outcome of one or more checks together with the packages which caused it.
+#[derive(PartialEq, Eq, Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CheckReport {
+    pub result: CheckResult,
+    pub packages: Vec<TriggeringPackage>,
+    /// Filled in by the kernel check, even if the running kernel is up to date.
+    pub kernel: Option<KernelVersions>,
+}
+
+impl CheckReport {
+    /// Add a triggering package and raise the result accordingly.
+    pub fn add_package(&mut self, package: TriggeringPackage) {
+        self.result = self.result.max(package.result);
+        self.packages.push(package);
+    }
+
+    /// Combine two reports, keeping the most severe result and all triggering packages.
+    pub fn merge(mut self, other: CheckReport) -> CheckReport {
+        self.result = self.result.max(other.result);
+        self.packages.extend(other.packages);
+        self.kernel = self.kernel.or(other.kernel);
+        self
+    }
+
+    /// Unix timestamp of the first upgrade since `boot_time` of a package which requires a
+    /// reboot.
+    ///
+    /// The install date only tells about the last upgrade of a package, so the pacman log is
+    /// asked first. The install date is the fallback if the log got rotated or the boot time is
+    /// unknown.
+    pub fn reboot_pending_since(
+        &self,
+        pacman_log: &PacmanLog,
+        boot_time: Option<i64>,
+    ) -> Option<i64> {
+        self.packages
+            .iter()
+            .filter(|package| package.result >= CheckResult::Reboot)
+            .filter_map(|package| {
+                boot_time
+                    .and_then(|boot_time| pacman_log.first_upgrade_since(&package.name, boot_time))
+                    .or(package.info.install_date)
+            })
+            .min()
+    }
+
+    /// One line per finding, e.g. "systemd 256.4-1 → 256.5-1 (2 hours ago)".
+    pub fn details(&self) -> Vec<String> {
+        let mut details = vec![];
+        if let Some(kernel) = &self.kernel
+            && self.result == CheckResult::KernelUpdate
+        {
+            details.push(tr_args(
+                "details-kernel",
+                &[
+                    ("running", kernel.running.as_str().into()),
+                    ("installed", kernel.installed.version.as_str().into()),
+                    ("ago", kernel.installed.installed_reltime().into()),
+                ],
+            ));
+        }
+        details.extend(
+            self.packages
+                .iter()
+                .filter(|package| package.result != CheckResult::KernelUpdate)
+                .map(TriggeringPackage::describe),
+        );
+        details
+    }
+
+    /// The notification body: what got updated, followed by what to do about it.
+    pub fn body(&self) -> String {
+        let mut lines = self.details();
+        lines.push(self.result.body());
+        lines.join("\n")
+    }
+}
+
 pub trait Check {
-    fn check(&self) -> CheckResult;
+    fn check(&self) -> CheckReport;
+}
+
+/// A package with version 1.0.0-1 for tests, change the fields as needed.
+#[cfg(test)]
+pub(crate) fn triggering_package(name: &str, result: CheckResult) -> TriggeringPackage {
+    TriggeringPackage {
+        name: name.to_owned(),
+        info: PackageInfo {
+            version: "1.0.0-1".to_owned(),
+            install_date: None,
+        },
+        previous_version: None,
+        result,
+    }
 }
 
 #[cfg(test)]
@@ -53,6 +199,94 @@ mod test {
         assert_eq!(results.iter().max(), Some(&CheckResult::KernelUpdate));
     }
 
+    #[test]
+    fn test_describe_package() {
+        let mut package = triggering_package("systemd", CheckResult::Reboot);
+        package.info.version = "256.5-1".to_owned();
+        assert_eq!(package.describe(), "systemd 256.5-1 (unknown)");
+        package.previous_version = Some("256.4-1".to_owned());
+        assert_eq!(package.describe(), "systemd 256.4-1 → 256.5-1 (unknown)");
+    }
+
+    #[test]
+    fn test_report_body_lists_findings() {
+        let mut report = CheckReport::default();
+        report.add_package(triggering_package("linux", CheckResult::KernelUpdate));
+        report.add_package(triggering_package("systemd", CheckResult::Reboot));
+        report.kernel = Some(KernelVersions {
+            running: "6.10.5.arch1.1".to_owned(),
+            installed: PackageInfo {
+                version: "6.10.6.arch1.1".to_owned(),
+                install_date: None,
+            },
+        });
+        assert_eq!(
+            report.body(),
+            "Kernel 6.10.5.arch1.1 running, 6.10.6.arch1.1 installed (unknown)\n\
+             systemd 1.0.0-1 (unknown)\n\
+             Kernel got updated. You should reboot your system!"
+        );
+    }
+
+    #[test]
+    fn test_report_merge_keeps_most_severe() {
+        let mut session = CheckReport::default();
+        session.add_package(triggering_package(
+            "xorg-server",
+            CheckResult::RestartSession,
+        ));
+        let mut kernel = CheckReport::default();
+        kernel.add_package(triggering_package("linux", CheckResult::KernelUpdate));
+
+        let merged = session.merge(kernel);
+        assert_eq!(merged.result, CheckResult::KernelUpdate);
+        assert_eq!(merged.packages.len(), 2);
+        assert_eq!(merged.packages[0].name, "xorg-server");
+    }
+
+    #[test]
+    fn test_reboot_pending_since_first_upgrade() {
+        let mut systemd = triggering_package("systemd", CheckResult::Reboot);
+        systemd.info.install_date = Some(1724163600);
+        let mut xorg = triggering_package("xorg-server", CheckResult::RestartSession);
+        xorg.info.install_date = Some(1724000000);
+        let mut report = CheckReport::default();
+        report.add_package(systemd);
+        report.add_package(xorg);
+        let pacman_log = PacmanLog::parse(
+            "[2024-08-20T10:00:00+0000] [ALPM] upgraded systemd (256.4-1 -> 256.5-1)\n\
+             [2024-08-20T14:20:00+0000] [ALPM] upgraded systemd (256.5-1 -> 256.5-2)\n",
+        );
+        // The second upgrade after boot set the install date.
+        assert_eq!(
+            report.reboot_pending_since(&pacman_log, Some(1724140800)),
+            Some(1724148000)
+        );
+        assert_eq!(
+            report.reboot_pending_since(&pacman_log, None),
+            Some(1724163600)
+        );
+        assert_eq!(
+            report.reboot_pending_since(&PacmanLog::default(), Some(1724140800)),
+            Some(1724163600)
+        );
+    }
+
+    #[test]
+    fn test_name_matches_serde() {
+        for result in [
+            CheckResult::Nothing,
+            CheckResult::RestartSession,
+            CheckResult::Reboot,
+            CheckResult::KernelUpdate,
+        ] {
+            assert_eq!(
+                serde_json::to_value(result).unwrap(),
+                serde_json::Value::from(result.name())
+            );
+        }
+    }
+
     #[test]
     fn test_summary() {
         assert_eq!(CheckResult::Nothing.summary(), "All good");
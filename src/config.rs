@@ -0,0 +1,252 @@
+use crate::checks::CheckResult;
+use anyhow::{Context, Result, anyhow};
+use notify_rust::{Hint, Notification};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Settings read from `$XDG_CONFIG_HOME/reboot-arch-btw/config.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub notification: NotificationStyles,
+    pub messages: MessageTemplates,
+}
+
+/// Templates for the summary and body of the message for each result.
+///
+/// See [`crate::template`] for the available placeholders.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MessageTemplates {
+    pub restart_session: MessageTemplate,
+    pub reboot: MessageTemplate,
+    pub kernel_update: MessageTemplate,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MessageTemplate {
+    pub summary: Option<String>,
+    pub body: Option<String>,
+}
+
+/// How the notification looks for each result.
+///
+/// Anything not set in the config file falls back to the built-in style for that result.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct NotificationStyles {
+    pub restart_session: NotificationStyle,
+    pub reboot: NotificationStyle,
+    pub kernel_update: NotificationStyle,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct NotificationStyle {
+    pub urgency: Option<Urgency>,
+    pub icon: Option<String>,
+    pub category: Option<String>,
+    /// Keep the notification around after one of its actions got invoked.
+    pub resident: Option<bool>,
+    /// Don't keep the notification in the notification history.
+    pub transient: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl From<Urgency> for notify_rust::Urgency {
+    fn from(urgency: Urgency) -> Self {
+        match urgency {
+            Urgency::Low => notify_rust::Urgency::Low,
+            Urgency::Normal => notify_rust::Urgency::Normal,
+            Urgency::Critical => notify_rust::Urgency::Critical,
+        }
+    }
+}
+
+/// Resolve an XDG base directory like `XDG_CONFIG_HOME`, falling back to `fallback` relative to
+/// the home directory if the variable isn't set.
+pub fn xdg_dir(variable: &str, fallback: &str) -> Result<PathBuf> {
+    match env::var_os(variable).filter(|dir| !dir.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(env::home_dir()
+            .ok_or_else(|| anyhow!("Could not determine home directory"))?
+            .join(fallback)),
+    }
+}
+
+/// Default path of the config file, usually `~/.config/reboot-arch-btw/config.toml`.
+pub fn default_config_path() -> Result<PathBuf> {
+    Ok(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("reboot-arch-btw/config.toml"))
+}
+
+impl Config {
+    /// Load the config from `path` or from the default location.
+    ///
+    /// A missing config file at the default location is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Config> {
+        let (path, required) = match path {
+            Some(path) => (path.to_owned(), true),
+            None => (default_config_path()?, false),
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                Self::parse(&content).with_context(|| anyhow!("Could not parse {}", path.display()))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound && !required => Ok(Config::default()),
+            Err(err) => Err(err).with_context(|| anyhow!("Could not read {}", path.display())),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Config> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+impl MessageTemplates {
+    pub fn for_result(&self, result: &CheckResult) -> Option<&MessageTemplate> {
+        match result {
+            CheckResult::Nothing => None,
+            CheckResult::RestartSession => Some(&self.restart_session),
+            CheckResult::Reboot => Some(&self.reboot),
+            CheckResult::KernelUpdate => Some(&self.kernel_update),
+        }
+    }
+}
+
+impl NotificationStyles {
+    /// The configured style for `result`, completed with the built-in defaults.
+    pub fn for_result(&self, result: &CheckResult) -> NotificationStyle {
+        let configured = match result {
+            CheckResult::Nothing => return NotificationStyle::default(),
+            CheckResult::RestartSession => &self.restart_session,
+            CheckResult::Reboot => &self.reboot,
+            CheckResult::KernelUpdate => &self.kernel_update,
+        };
+        configured
+            .clone()
+            .or(NotificationStyle::default_for(result))
+    }
+}
+
+impl NotificationStyle {
+    /// The built-in style: a kernel update is critical and should stay on screen, while a session
+    /// restart is only a low urgency reminder.
+    pub fn default_for(result: &CheckResult) -> NotificationStyle {
+        let (urgency, icon, category) = match result {
+            CheckResult::Nothing => return NotificationStyle::default(),
+            CheckResult::RestartSession => (
+                Urgency::Low,
+                "system-log-out",
+                "x-reboot-arch-btw.restart-session",
+            ),
+            CheckResult::Reboot => (Urgency::Normal, "system-reboot", "x-reboot-arch-btw.reboot"),
+            CheckResult::KernelUpdate => (
+                Urgency::Critical,
+                "system-reboot",
+                "x-reboot-arch-btw.kernel-update",
+            ),
+        };
+        NotificationStyle {
+            urgency: Some(urgency),
+            icon: Some(icon.to_owned()),
+            category: Some(category.to_owned()),
+            resident: None,
+            transient: None,
+        }
+    }
+
+    /// Fill everything not set in `self` from `other`.
+    pub fn or(self, other: NotificationStyle) -> NotificationStyle {
+        NotificationStyle {
+            urgency: self.urgency.or(other.urgency),
+            icon: self.icon.or(other.icon),
+            category: self.category.or(other.category),
+            resident: self.resident.or(other.resident),
+            transient: self.transient.or(other.transient),
+        }
+    }
+
+    pub fn apply(&self, notification: &mut Notification) {
+        if let Some(urgency) = self.urgency {
+            notification.urgency(urgency.into());
+        }
+        if let Some(icon) = &self.icon {
+            notification.icon(icon);
+        }
+        if let Some(category) = &self.category {
+            notification.hint(Hint::Category(category.clone()));
+        }
+        if let Some(resident) = self.resident {
+            notification.hint(Hint::Resident(resident));
+        }
+        if let Some(transient) = self.transient {
+            notification.hint(Hint::Transient(transient));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        let style = config.notification.for_result(&CheckResult::KernelUpdate);
+        assert_eq!(
+            style,
+            NotificationStyle::default_for(&CheckResult::KernelUpdate)
+        );
+        assert_eq!(style.urgency, Some(Urgency::Critical));
+    }
+
+    #[test]
+    fn test_config_overrides_single_fields() {
+        let config = Config::parse(
+            r#"
+            [notification.restart-session]
+            urgency = "normal"
+            transient = true
+            "#,
+        )
+        .unwrap();
+        let style = config.notification.for_result(&CheckResult::RestartSession);
+        assert_eq!(style.urgency, Some(Urgency::Normal));
+        assert_eq!(style.transient, Some(true));
+        assert_eq!(style.icon.as_deref(), Some("system-log-out"));
+    }
+
+    #[test]
+    fn test_config_message_templates() {
+        let config = Config::parse(
+            r#"
+            [messages.reboot]
+            summary = "IT: please reboot before Friday"
+            "#,
+        )
+        .unwrap();
+        let template = config.messages.for_result(&CheckResult::Reboot).unwrap();
+        assert_eq!(
+            template.summary.as_deref(),
+            Some("IT: please reboot before Friday")
+        );
+        assert_eq!(template.body, None);
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_fields() {
+        assert!(Config::parse("[notification.reboot]\ncolour = \"red\"").is_err());
+        assert!(Config::parse("[notification.reboot]\nurgency = \"urgent\"").is_err());
+    }
+}
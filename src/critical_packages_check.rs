@@ -1,73 +1,95 @@
-use crate::checks::{Check, CheckResult};
+use crate::checks::{Check, CheckReport, CheckResult, TriggeringPackage};
 use crate::package::{PackageInfo, get_package_version};
+use crate::package_list::PackageRule;
+use crate::pacman_log::PacmanLog;
 use crate::session::SessionInfo;
 use anyhow::Result;
 use log::{info, warn};
 
 pub struct CriticalPackagesCheck<'a> {
     /// Compares the installation time of packages to the time since the last boot.
-    reboot_package_names: Vec<String>,
-    restart_session_package_names: Vec<String>,
+    reboot_packages: Vec<PackageRule>,
+    restart_session_packages: Vec<PackageRule>,
     session_info: SessionInfo,
     alpm_db: &'a alpm::Db,
-    verbose: bool,
+    /// Used to find the version which got replaced by an update.
+    pacman_log: &'a PacmanLog,
 }
 
-impl CriticalPackagesCheck<'_> {
+impl<'a> CriticalPackagesCheck<'a> {
     pub fn new(
-        reboot_package_names: Vec<String>,
-        restart_session_package_names: Vec<String>,
-        alpm_db: &alpm::Db,
-        verbose: bool,
-    ) -> Result<CriticalPackagesCheck<'_>> {
+        reboot_packages: Vec<PackageRule>,
+        restart_session_packages: Vec<PackageRule>,
+        alpm_db: &'a alpm::Db,
+        pacman_log: &'a PacmanLog,
+    ) -> Result<CriticalPackagesCheck<'a>> {
         let session_info = SessionInfo::from_utmp()?;
         Ok(CriticalPackagesCheck {
-            reboot_package_names,
-            restart_session_package_names,
+            reboot_packages,
+            restart_session_packages,
             session_info,
             alpm_db,
-            verbose,
+            pacman_log,
         })
     }
 
-    fn check_package_list(&self, package_list: &[String], max_install_date: i64) -> bool {
-        for package_name in package_list {
-            info!("Checking {package_name}");
-            match get_package_version(self.alpm_db, package_name) {
+    fn check_package_list(
+        &self,
+        package_list: &[PackageRule],
+        max_install_date: i64,
+        result: CheckResult,
+        report: &mut CheckReport,
+    ) {
+        for PackageRule { name, min_change } in package_list {
+            info!("Checking {name}");
+            match get_package_version(self.alpm_db, name) {
                 Ok(
                     package_info @ PackageInfo {
                         install_date: Some(install_date),
                         ..
                     },
                 ) => {
-                    if install_date > max_install_date {
-                        if self.verbose {
-                            println!(
-                                "{package_name} updated {}",
-                                package_info.installed_reltime()
-                            );
-                        }
-                        return true;
+                    if install_date <= max_install_date {
+                        continue;
                     }
+                    let previous_version = self.pacman_log.version_at(name, max_install_date);
+                    if !min_change.triggered_by(previous_version, &package_info.version) {
+                        info!("Ignoring {name}, changed less than {min_change:?}");
+                        continue;
+                    }
+                    report.add_package(TriggeringPackage {
+                        name: name.clone(),
+                        previous_version: previous_version.map(str::to_owned),
+                        info: package_info,
+                        result,
+                    });
                 }
-                _ => warn!("Failed to get package info for {package_name}"),
+                _ => warn!("Failed to get package info for {name}"),
             }
         }
-        false
     }
 }
 
 impl Check for CriticalPackagesCheck<'_> {
-    fn check(&self) -> CheckResult {
+    fn check(&self) -> CheckReport {
         let boot_time = self.session_info.boot_time.unix_timestamp();
-        let session_time = self.session_info.session_time.unix_timestamp();
 
-        if self.check_package_list(&self.reboot_package_names, boot_time) {
-            return CheckResult::Reboot;
-        }
-        if self.check_package_list(&self.restart_session_package_names, session_time) {
-            return CheckResult::RestartSession;
+        let mut report = CheckReport::default();
+        self.check_package_list(
+            &self.reboot_packages,
+            boot_time,
+            CheckResult::Reboot,
+            &mut report,
+        );
+        match self.session_info.session_time {
+            Some(session_time) => self.check_package_list(
+                &self.restart_session_packages,
+                session_time.unix_timestamp(),
+                CheckResult::RestartSession,
+                &mut report,
+            ),
+            None => info!("Nobody is logged in, not checking session restart packages"),
         }
-        CheckResult::Nothing
+        report
     }
 }
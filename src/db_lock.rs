@@ -0,0 +1,97 @@
+use log::info;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// pacman holds this lock while a transaction is in progress.
+pub const PACMAN_DB_LOCK_PATH: &str = "/var/lib/pacman/db.lck";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait until `lock` is gone, for at most `timeout`.
+///
+/// Returns `false` if the lock is still held afterwards. While it is held the local database may
+/// be half updated, so checking it could give a wrong result.
+pub fn wait_for_unlock(lock: &Path, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    if lock.exists() {
+        info!("Waiting for {} to be removed", lock.display());
+    }
+    while lock.exists() {
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+    true
+}
+
+/// Whether we run below a pacman process, e.g. from a hook.
+///
+/// pacman holds the lock while running hooks, so waiting for it would only hold up the transaction.
+pub fn pacman_is_ancestor() -> bool {
+    let mut pid = parent_pid("self");
+    while let Some(current) = pid.filter(|&pid| pid > 1) {
+        let comm = fs::read_to_string(format!("/proc/{current}/comm")).unwrap_or_default();
+        if comm.trim_end() == "pacman" {
+            info!("Running below pacman process {current}");
+            return true;
+        }
+        pid = parent_pid(&current.to_string());
+    }
+    false
+}
+
+fn parent_pid(pid: &str) -> Option<u32> {
+    parse_parent_pid(&fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)
+}
+
+/// The parent PID from the content of `/proc/<pid>/stat`, which is the fourth field.
+fn parse_parent_pid(stat: &str) -> Option<u32> {
+    // The command name in the second field may contain spaces and parentheses.
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use std::fs;
+
+    #[test]
+    fn test_unlocked() {
+        let dir = TestDir::new("unlocked");
+        let lock = dir.path().join("db.lck");
+        assert!(wait_for_unlock(&lock, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_locked_until_timeout() {
+        let dir = TestDir::new("locked");
+        let lock = dir.path().join("db.lck");
+        fs::write(&lock, "").unwrap();
+        let locked = wait_for_unlock(&lock, Duration::from_millis(10));
+        assert!(!locked);
+    }
+
+    #[test]
+    fn test_parse_parent_pid() {
+        assert_eq!(
+            parse_parent_pid("1234 (sudo) S 1200 1234 1200 0 -1 4194560"),
+            Some(1200)
+        );
+        assert_eq!(
+            parse_parent_pid("1234 (a) b (c) S 1200 1234 1200 0 -1 4194560"),
+            Some(1200)
+        );
+        assert_eq!(parse_parent_pid(""), None);
+    }
+
+    #[test]
+    fn test_not_below_pacman() {
+        assert!(!pacman_is_ancestor());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::i18n::tr_args;
+use anyhow::{Result, anyhow, bail};
+use std::time::Duration;
+
+/// Parse a human readable duration like "90", "30m", "1h30m" or "2d".
+///
+/// A number without unit is interpreted as seconds. Supported units are `s`, `m`, `h`, `d` and
+/// `w`.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("Empty duration");
+    }
+    if let Ok(secs) = input.parse() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut remaining = input;
+    while !remaining.is_empty() {
+        let digits = remaining
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(remaining.len());
+        if digits == 0 {
+            bail!("Expected a number in duration '{input}'");
+        }
+        let value: u64 = remaining[..digits].parse()?;
+        remaining = &remaining[digits..];
+        let unit = remaining
+            .chars()
+            .next()
+            .ok_or_else(|| anyhow!("Missing unit in duration '{input}'"))?;
+        let factor = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 3600 * 24,
+            'w' => 3600 * 24 * 7,
+            _ => bail!("Unknown unit '{unit}' in duration '{input}'"),
+        };
+        remaining = &remaining[unit.len_utf8()..];
+        total = value
+            .checked_mul(factor)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(|| anyhow!("Duration '{input}' is too long"))?;
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Split a number of seconds into a rough count and its unit.
+fn rough_duration(secs: u64) -> (u64, &'static str) {
+    if secs < 60 {
+        (secs, "seconds")
+    } else if secs < 7200 {
+        (secs / 60, "minutes")
+    } else if secs < 3600 * 36 {
+        (secs / 3600, "hours")
+    } else {
+        (secs / (3600 * 24), "days")
+    }
+}
+
+/// Format a number of seconds roughly, e.g. "5 minutes" or "3 days".
+pub fn format_duration(secs: u64) -> String {
+    let (count, unit) = rough_duration(secs);
+    tr_args(&format!("duration-{unit}"), &[("count", count.into())])
+}
+
+/// Format a number of seconds in the past roughly, e.g. "5 minutes ago" or "3 days ago".
+pub fn format_duration_ago(secs: u64) -> String {
+    let (count, unit) = rough_duration(secs);
+    tr_args(&format!("ago-{unit}"), &[("count", count.into())])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_plain_seconds() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86400));
+        assert_eq!(parse_duration("1w").unwrap(), Duration::from_secs(604800));
+    }
+
+    #[test]
+    fn test_parse_duration_combined() {
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("5").is_ok());
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(5), "5 seconds");
+        assert_eq!(format_duration(150), "2 minutes");
+        assert_eq!(format_duration(3600 * 3 + 100), "3 hours");
+        assert_eq!(format_duration(3600 * 24 * 5 + 100), "5 days");
+        assert_eq!(format_duration(3600 * 24 + 10), "24 hours");
+    }
+
+    #[test]
+    fn test_format_duration_ago() {
+        assert_eq!(format_duration_ago(1), "1 second ago");
+        assert_eq!(format_duration_ago(3600 * 24 * 2), "2 days ago");
+    }
+}
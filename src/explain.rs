@@ -0,0 +1,232 @@
+//! Explain which packages got installed since boot and since the session started.
+//!
+//! Meant to tune the package lists against what actually got updated, so packages which are in no
+//! list but look relevant get marked as well.
+
+use crate::checks::CheckResult;
+use crate::package::PackageInfo;
+use crate::session::SessionInfo;
+use time::OffsetDateTime;
+
+/// Why a package which is in no list might still require a restart.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Relevance {
+    /// Ships shared libraries, which running programs keep using in their old version.
+    Library,
+    /// Ships systemd system services, which keep running in their old version.
+    Service,
+    LibraryAndService,
+}
+
+impl Relevance {
+    /// Detect shared libraries in `/usr/lib` and system services from the package files.
+    pub fn from_files<'a>(files: impl IntoIterator<Item = &'a str>) -> Option<Relevance> {
+        let mut library = false;
+        let mut service = false;
+        for file in files {
+            if let Some(name) = file.strip_prefix("usr/lib/")
+                && !name.contains('/')
+                && name.contains(".so")
+            {
+                library = true;
+            }
+            if let Some(name) = file.strip_prefix("usr/lib/systemd/system/")
+                && !name.contains('/')
+                && name.ends_with(".service")
+            {
+                service = true;
+            }
+        }
+        match (library, service) {
+            (true, true) => Some(Relevance::LibraryAndService),
+            (true, false) => Some(Relevance::Library),
+            (false, true) => Some(Relevance::Service),
+            (false, false) => None,
+        }
+    }
+
+    /// The kebab case name used in the messages, e.g. "library".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Relevance::Library => "library",
+            Relevance::Service => "service",
+            Relevance::LibraryAndService => "library-and-service",
+        }
+    }
+}
+
+/// The rules of `KernelChecker` and `CriticalPackagesCheck`.
+pub struct Rules {
+    /// The package of the running kernel, if it could be detected.
+    pub kernel_package: Option<String>,
+    pub reboot_package_names: Vec<String>,
+    pub restart_session_package_names: Vec<String>,
+}
+
+impl Rules {
+    /// What an update of `package_name` requires according to the lists.
+    pub fn classify(&self, package_name: &str) -> CheckResult {
+        let listed = |names: &[String]| names.iter().any(|name| name == package_name);
+        if self.kernel_package.as_deref() == Some(package_name) {
+            CheckResult::KernelUpdate
+        } else if listed(&self.reboot_package_names) {
+            CheckResult::Reboot
+        } else if listed(&self.restart_session_package_names) {
+            CheckResult::RestartSession
+        } else {
+            CheckResult::Nothing
+        }
+    }
+}
+
+/// A package installed since boot.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ExplainedPackage {
+    pub name: String,
+    pub info: PackageInfo,
+    /// What the lists say about it.
+    pub result: CheckResult,
+    /// Only for packages in no list.
+    pub relevance: Option<Relevance>,
+}
+
+/// The packages installed since boot, each sorted by install date.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct Explanation {
+    pub since_session: Vec<ExplainedPackage>,
+    /// Installed after boot, but before the session started.
+    pub before_session: Vec<ExplainedPackage>,
+}
+
+/// Classify all packages of `db` which got installed since boot.
+pub fn explain(db: &alpm::Db, session_info: SessionInfo, rules: &Rules) -> Explanation {
+    let boot_time = session_info.boot_time.unix_timestamp();
+    let packages = db
+        .pkgs()
+        .iter()
+        .filter(|package| package.install_date().is_some_and(|date| date > boot_time))
+        .map(|package| {
+            let result = rules.classify(package.name());
+            let relevance = if result == CheckResult::Nothing {
+                let files = package.files();
+                Relevance::from_files(
+                    files
+                        .files()
+                        .iter()
+                        .filter_map(|file| std::str::from_utf8(file.name()).ok()),
+                )
+            } else {
+                None
+            };
+            ExplainedPackage {
+                name: package.name().to_owned(),
+                info: PackageInfo::from_package(package),
+                result,
+                relevance,
+            }
+        })
+        .collect();
+    group(
+        packages,
+        session_info
+            .session_time
+            .map(OffsetDateTime::unix_timestamp),
+    )
+}
+
+/// Without a session everything counts as installed before it.
+fn group(mut packages: Vec<ExplainedPackage>, session_time: Option<i64>) -> Explanation {
+    packages.sort_by_key(|package| package.info.install_date);
+    let (since_session, before_session) = packages.into_iter().partition(|package| {
+        session_time.is_some_and(|session_time| package.info.install_date > Some(session_time))
+    });
+    Explanation {
+        since_session,
+        before_session,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rules() -> Rules {
+        Rules {
+            kernel_package: Some("linux".to_owned()),
+            reboot_package_names: vec!["systemd".to_owned()],
+            restart_session_package_names: vec!["xorg-server".to_owned()],
+        }
+    }
+
+    fn explained(name: &str, install_date: i64) -> ExplainedPackage {
+        ExplainedPackage {
+            name: name.to_owned(),
+            info: PackageInfo {
+                version: "1.0-1".to_owned(),
+                install_date: Some(install_date),
+            },
+            result: rules().classify(name),
+            relevance: None,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let rules = rules();
+        assert_eq!(rules.classify("linux"), CheckResult::KernelUpdate);
+        assert_eq!(rules.classify("systemd"), CheckResult::Reboot);
+        assert_eq!(rules.classify("xorg-server"), CheckResult::RestartSession);
+        assert_eq!(rules.classify("firefox"), CheckResult::Nothing);
+    }
+
+    #[test]
+    fn test_relevance_from_files() {
+        assert_eq!(
+            Relevance::from_files(["usr/", "usr/lib/", "usr/lib/libssl.so.3"]),
+            Some(Relevance::Library)
+        );
+        assert_eq!(
+            Relevance::from_files(["usr/lib/systemd/system/sshd.service", "usr/lib/libcrypt.so"]),
+            Some(Relevance::LibraryAndService)
+        );
+        assert_eq!(
+            Relevance::from_files(["usr/lib/systemd/system/sshd.service"]),
+            Some(Relevance::Service)
+        );
+        // Plugins and user services don't count.
+        assert_eq!(
+            Relevance::from_files([
+                "usr/lib/firefox/libxul.so",
+                "usr/lib/systemd/user/pipewire.service",
+                "usr/bin/firefox",
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_group() {
+        let explanation = group(
+            vec![
+                explained("firefox", 300),
+                explained("systemd", 100),
+                explained("xorg-server", 250),
+            ],
+            Some(200),
+        );
+        assert_eq!(
+            explanation,
+            Explanation {
+                since_session: vec![explained("xorg-server", 250), explained("firefox", 300)],
+                before_session: vec![explained("systemd", 100)],
+            }
+        );
+        assert_eq!(
+            group(vec![explained("xorg-server", 250)], None),
+            Explanation {
+                since_session: vec![],
+                before_session: vec![explained("xorg-server", 250)],
+            }
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! History of the results in `history.jsonl` in the state directory.
+//!
+//! A record gets appended whenever the result changes and when a reboot ended a pending result, so
+//! we can tell how long reboots stayed pending.
+
+use crate::checks::{CheckReport, CheckResult};
+use crate::state::state_dir;
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+/// A line of the history file.
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Record {
+    /// The result changed.
+    Changed {
+        /// Unix timestamp of the change, which for a reboot is when it first became required.
+        time: i64,
+        boot_id: String,
+        result: CheckResult,
+        /// The packages which caused the result.
+        packages: Vec<String>,
+    },
+    /// The system got rebooted while a result was pending.
+    Rebooted {
+        /// Unix timestamp of the boot.
+        time: i64,
+        boot_id: String,
+    },
+}
+
+/// A time span during which a reboot or session restart was required.
+#[derive(PartialEq, Eq, Debug)]
+pub struct PendingPeriod {
+    pub start: i64,
+    /// When it got resolved, `None` if it still is pending.
+    pub end: Option<i64>,
+    /// The most severe result during the period.
+    pub result: CheckResult,
+    pub packages: Vec<String>,
+}
+
+pub fn path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("history.jsonl"))
+}
+
+pub fn load(path: &Path) -> Result<Vec<Record>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err).with_context(|| anyhow!("Could not read {}", path.display())),
+    };
+    // A truncated line, e.g. after a crash, must not make the whole history unusable.
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .map_err(|err| warn!("Skipping invalid line in {}: {err}", path.display()))
+                .ok()
+        })
+        .collect())
+}
+
+/// The records to add to the history at `path` for `report`, empty if nothing changed.
+///
+/// `time` is when the result changed, see `Record::Changed`.
+pub fn changes(
+    path: &Path,
+    report: &CheckReport,
+    boot_id: &str,
+    boot_time: i64,
+    time: i64,
+) -> Result<Vec<Record>> {
+    let records = load(path)?;
+    Ok(new_records(
+        records.last(),
+        report,
+        boot_id,
+        boot_time,
+        time,
+    ))
+}
+
+/// Append `new_records` to the history at `path`.
+pub fn append(path: &Path, new_records: &[Record]) -> Result<()> {
+    if new_records.is_empty() {
+        return Ok(());
+    }
+    let dir = path.parent().expect("history file has a parent directory");
+    fs::create_dir_all(dir).with_context(|| anyhow!("Could not create {}", dir.display()))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| anyhow!("Could not open {}", path.display()))?;
+    for record in new_records {
+        writeln!(file, "{}", serde_json::to_string(record)?)
+            .with_context(|| anyhow!("Could not write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn new_records(
+    last: Option<&Record>,
+    report: &CheckReport,
+    boot_id: &str,
+    boot_time: i64,
+    time: i64,
+) -> Vec<Record> {
+    let mut records = vec![];
+    let last_result = match last {
+        Some(Record::Changed {
+            boot_id: last_boot_id,
+            result,
+            ..
+        }) if last_boot_id != boot_id => {
+            if *result > CheckResult::Nothing {
+                records.push(Record::Rebooted {
+                    time: boot_time,
+                    boot_id: boot_id.to_owned(),
+                });
+            }
+            CheckResult::Nothing
+        }
+        Some(Record::Changed { result, .. }) => *result,
+        Some(Record::Rebooted { .. }) | None => CheckResult::Nothing,
+    };
+    if report.result != last_result {
+        records.push(Record::Changed {
+            time,
+            boot_id: boot_id.to_owned(),
+            result: report.result,
+            packages: report
+                .packages
+                .iter()
+                .map(|package| package.name.clone())
+                .collect(),
+        });
+    }
+    records
+}
+
+/// Group the records into the periods during which something was pending.
+pub fn pending_periods(records: &[Record]) -> Vec<PendingPeriod> {
+    let mut periods = vec![];
+    let mut current: Option<PendingPeriod> = None;
+    for record in records {
+        match record {
+            Record::Changed {
+                time,
+                result,
+                packages,
+                ..
+            } if *result > CheckResult::Nothing => {
+                let period = current.get_or_insert_with(|| PendingPeriod {
+                    start: *time,
+                    end: None,
+                    result: *result,
+                    packages: vec![],
+                });
+                period.result = period.result.max(*result);
+                for package in packages {
+                    if !period.packages.contains(package) {
+                        period.packages.push(package.clone());
+                    }
+                }
+            }
+            Record::Changed { time, .. } | Record::Rebooted { time, .. } => {
+                if let Some(mut period) = current.take() {
+                    period.end = Some(*time);
+                    periods.push(period);
+                }
+            }
+        }
+    }
+    periods.extend(current);
+    periods
+}
+
+/// How long the resolved periods with `result` stayed pending, in seconds.
+pub fn resolved_durations(periods: &[PendingPeriod], result: CheckResult) -> Vec<i64> {
+    periods
+        .iter()
+        .filter(|period| period.result == result)
+        .filter_map(|period| Some(period.end? - period.start))
+        .collect()
+}
+
+/// Format a unix timestamp like "2024-08-20 08:12 UTC".
+pub fn format_time(time: i64) -> String {
+    let format =
+        time::format_description::parse_borrowed::<2>("[year]-[month]-[day] [hour]:[minute] UTC")
+            .expect("valid format description");
+    OffsetDateTime::from_unix_timestamp(time)
+        .ok()
+        .and_then(|time| time.format(&format).ok())
+        .unwrap_or_else(|| time.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::triggering_package;
+    use crate::test_dir::TestDir;
+
+    fn report(result: CheckResult, packages: &[&str]) -> CheckReport {
+        let mut report = CheckReport::default();
+        for name in packages {
+            report.add_package(triggering_package(name, result));
+        }
+        report
+    }
+
+    fn changed(time: i64, boot_id: &str, result: CheckResult, packages: &[&str]) -> Record {
+        Record::Changed {
+            time,
+            boot_id: boot_id.to_owned(),
+            result,
+            packages: packages.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_new_records_on_change_only() {
+        let nothing = CheckReport::default();
+        assert_eq!(new_records(None, &nothing, "a", 0, 10), vec![]);
+
+        let kernel_update = report(CheckResult::KernelUpdate, &["linux"]);
+        let records = new_records(None, &kernel_update, "a", 0, 10);
+        assert_eq!(
+            records,
+            vec![changed(10, "a", CheckResult::KernelUpdate, &["linux"])]
+        );
+        assert_eq!(
+            new_records(records.last(), &kernel_update, "a", 0, 20),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_new_records_after_reboot() {
+        let last = changed(10, "a", CheckResult::KernelUpdate, &["linux"]);
+        assert_eq!(
+            new_records(Some(&last), &CheckReport::default(), "b", 100, 200),
+            vec![Record::Rebooted {
+                time: 100,
+                boot_id: "b".to_owned()
+            }]
+        );
+
+        // Something new right after the reboot.
+        let reboot = report(CheckResult::Reboot, &["systemd"]);
+        assert_eq!(
+            new_records(Some(&last), &reboot, "b", 100, 200),
+            vec![
+                Record::Rebooted {
+                    time: 100,
+                    boot_id: "b".to_owned()
+                },
+                changed(200, "b", CheckResult::Reboot, &["systemd"])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending_periods() {
+        let records = [
+            changed(10, "a", CheckResult::RestartSession, &["xorg-server"]),
+            changed(
+                20,
+                "a",
+                CheckResult::KernelUpdate,
+                &["xorg-server", "linux"],
+            ),
+            Record::Rebooted {
+                time: 100,
+                boot_id: "b".to_owned(),
+            },
+            changed(200, "b", CheckResult::Reboot, &["systemd"]),
+        ];
+        assert_eq!(
+            pending_periods(&records),
+            vec![
+                PendingPeriod {
+                    start: 10,
+                    end: Some(100),
+                    result: CheckResult::KernelUpdate,
+                    packages: vec!["xorg-server".to_owned(), "linux".to_owned()],
+                },
+                PendingPeriod {
+                    start: 200,
+                    end: None,
+                    result: CheckResult::Reboot,
+                    packages: vec!["systemd".to_owned()],
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolved_durations() {
+        let period = |start, end, result| PendingPeriod {
+            start,
+            end,
+            result,
+            packages: vec![],
+        };
+        let periods = [
+            period(10, Some(100), CheckResult::KernelUpdate),
+            period(200, Some(250), CheckResult::Reboot),
+            period(300, Some(400), CheckResult::KernelUpdate),
+            period(500, None, CheckResult::KernelUpdate),
+        ];
+        assert_eq!(
+            resolved_durations(&periods, CheckResult::KernelUpdate),
+            vec![90, 100]
+        );
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(1724141553), "2024-08-20 08:12 UTC");
+    }
+
+    fn record(
+        path: &Path,
+        report: &CheckReport,
+        boot_id: &str,
+        boot_time: i64,
+        time: i64,
+    ) -> Vec<Record> {
+        let records = changes(path, report, boot_id, boot_time, time).unwrap();
+        append(path, &records).unwrap();
+        records
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let dir = TestDir::new("history");
+        let path = dir.path().join("history/history.jsonl");
+        let kernel_update = report(CheckResult::KernelUpdate, &["linux"]);
+        assert_eq!(
+            record(&path, &kernel_update, "a", 0, 10),
+            vec![changed(10, "a", CheckResult::KernelUpdate, &["linux"])]
+        );
+        assert_eq!(record(&path, &kernel_update, "a", 0, 20), vec![]);
+        record(&path, &CheckReport::default(), "b", 100, 200);
+        assert_eq!(
+            load(&path).unwrap(),
+            vec![
+                changed(10, "a", CheckResult::KernelUpdate, &["linux"]),
+                Record::Rebooted {
+                    time: 100,
+                    boot_id: "b".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_skips_invalid_lines() {
+        let dir = TestDir::new("history-invalid");
+        let path = dir.path().join("history.jsonl");
+        fs::write(
+            &path,
+            "{\"event\":\"rebooted\",\"time\":100,\"boot_id\":\"b\"}\n\
+             {\"event\":\"changed\",\"time\":20\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load(&path).unwrap(),
+            vec![Record::Rebooted {
+                time: 100,
+                boot_id: "b".to_owned()
+            }]
+        );
+        let kernel_update = report(CheckResult::KernelUpdate, &["linux"]);
+        assert_eq!(
+            record(&path, &kernel_update, "b", 100, 200),
+            vec![changed(200, "b", CheckResult::KernelUpdate, &["linux"])]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Support for running as pacman hook with `NeedsTargets`.
+//!
+//! pacman passes the names of the packages of the transaction on stdin, one per line. Only those
+//! get checked, so a big upgrade without anything relevant reports nothing.
+
+use crate::package_list::PackageRule;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use log::info;
+use std::io::BufRead;
+
+/// When the hook runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HookMode {
+    /// Before the transaction: warn about upgrades which break loading kernel modules.
+    Pre,
+    /// After the transaction: check whether a reboot or session restart is needed.
+    Post,
+}
+
+/// Upgrades which break loading kernel modules until the next reboot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ModuleBreakage {
+    /// The package of the running kernel gets upgraded, which removes its modules.
+    Kernel,
+    /// A package shipping modules for the running kernel, e.g. an out-of-tree driver, gets
+    /// upgraded.
+    Modules,
+}
+
+/// Find the targets of a pending transaction which break loading kernel modules.
+///
+/// `kernel_release` is the release of the running kernel as printed by `uname -r`.
+pub fn pending_breakages(
+    db: &alpm::Db,
+    kernel_package: &str,
+    kernel_release: &str,
+    targets: &[String],
+) -> Vec<(ModuleBreakage, String)> {
+    let modules_dir = format!("usr/lib/modules/{kernel_release}/");
+    targets
+        .iter()
+        .filter_map(|target| {
+            // Packages which are not installed yet can't remove any modules.
+            let ships_modules = db.pkg(target.as_str()).is_ok_and(|package| {
+                package
+                    .files()
+                    .files()
+                    .iter()
+                    .any(|file| file.name().starts_with(modules_dir.as_bytes()))
+            });
+            let breakage = classify_target(target, kernel_package, ships_modules)?;
+            info!("Upgrading {target} breaks loading modules: {breakage:?}");
+            Some((breakage, target.clone()))
+        })
+        .collect()
+}
+
+fn classify_target(
+    target: &str,
+    kernel_package: &str,
+    ships_modules: bool,
+) -> Option<ModuleBreakage> {
+    if target == kernel_package {
+        Some(ModuleBreakage::Kernel)
+    } else if ships_modules {
+        Some(ModuleBreakage::Modules)
+    } else {
+        None
+    }
+}
+
+/// Read the package names pacman passes on stdin.
+pub fn read_targets(input: impl BufRead) -> Result<Vec<String>> {
+    let mut targets = vec![];
+    for line in input.lines() {
+        let line = line.context("Could not read hook targets")?;
+        let target = line.trim();
+        if !target.is_empty() {
+            targets.push(target.to_owned());
+        }
+    }
+    Ok(targets)
+}
+
+/// The packages of `package_list` which are part of the transaction.
+pub fn filter_targets(package_list: &[PackageRule], targets: &[String]) -> Vec<PackageRule> {
+    package_list
+        .iter()
+        .filter(|rule| targets.contains(&rule.name))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_read_targets() {
+        let input = "linux\nsystemd\n\n  firefox \n";
+        assert_eq!(
+            read_targets(input.as_bytes()).unwrap(),
+            strings(&["linux", "systemd", "firefox"])
+        );
+    }
+
+    fn rules(names: &[&str]) -> Vec<PackageRule> {
+        names
+            .iter()
+            .map(|name| PackageRule {
+                name: name.to_string(),
+                min_change: Default::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_targets() {
+        let reboot_packages = rules(&["systemd", "linux-firmware", "amd-ucode"]);
+        let targets = strings(&["firefox", "systemd", "amd-ucode"]);
+        assert_eq!(
+            filter_targets(&reboot_packages, &targets),
+            rules(&["systemd", "amd-ucode"])
+        );
+        assert!(filter_targets(&reboot_packages, &strings(&["firefox"])).is_empty());
+    }
+
+    #[test]
+    fn test_classify_target() {
+        assert_eq!(
+            classify_target("linux", "linux", true),
+            Some(ModuleBreakage::Kernel)
+        );
+        assert_eq!(
+            classify_target("nvidia", "linux", true),
+            Some(ModuleBreakage::Modules)
+        );
+        assert_eq!(classify_target("linux-lts", "linux", false), None);
+        assert_eq!(classify_target("firefox", "linux", false), None);
+    }
+}
@@ -0,0 +1,182 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use log::{info, warn};
+use std::env;
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// The shipped Fluent catalogs. English is the fallback for anything missing in the others.
+const CATALOGS: [(&str, &str); 3] = [
+    ("en", include_str!("../i18n/en.ftl")),
+    ("de", include_str!("../i18n/de.ftl")),
+    ("fr", include_str!("../i18n/fr.ftl")),
+];
+
+struct Localizer {
+    /// The bundle of the selected language followed by the English fallback.
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+/// Select the language from `LC_ALL`, `LC_MESSAGES` or `LANG`.
+///
+/// Until this gets called all messages are in English.
+pub fn init() {
+    let language = language_from_env();
+    info!("Using language {language}");
+    LOCALIZER.set(Localizer::new(&language)).ok();
+}
+
+/// Get the localized message `id`.
+pub fn tr(id: &str) -> String {
+    tr_args(id, &[])
+}
+
+/// Get the localized message `id` with the given arguments filled in.
+pub fn tr_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let localizer = LOCALIZER.get_or_init(|| Localizer::new("en"));
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    for bundle in &localizer.bundles {
+        if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+            let mut errors = vec![];
+            let message = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+            for error in errors {
+                warn!("Could not format message {id}: {error}");
+            }
+            return message.into_owned();
+        }
+    }
+    warn!("Missing message {id}");
+    id.to_owned()
+}
+
+impl Localizer {
+    fn new(language: &str) -> Localizer {
+        let mut languages = vec![language];
+        if language != "en" {
+            languages.push("en");
+        }
+        let bundles = languages
+            .into_iter()
+            .filter_map(|language| {
+                let (_, catalog) = CATALOGS.iter().find(|(name, _)| *name == language)?;
+                Some(bundle(language, catalog))
+            })
+            .collect();
+        Localizer { bundles }
+    }
+}
+
+fn bundle(language: &str, catalog: &str) -> FluentBundle<FluentResource> {
+    let language: LanguageIdentifier = language.parse().expect("valid language identifier");
+    let resource = FluentResource::try_new(catalog.to_owned()).expect("shipped catalogs are valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // The isolation marks only help when mixing writing directions and show up as garbage in
+    // terminals.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("shipped catalogs have no duplicate messages");
+    bundle
+}
+
+fn language_from_env() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|variable| env::var(variable).ok())
+        .find(|value| !value.is_empty())
+        .map_or_else(|| "en".to_owned(), |locale| language_from_locale(&locale))
+}
+
+/// Extract the language of a POSIX locale like "de_CH.UTF-8".
+fn language_from_locale(locale: &str) -> String {
+    let language = locale
+        .split(['_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if CATALOGS.iter().any(|(name, _)| *name == language) {
+        language
+    } else {
+        "en".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format(language: &str, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let localizer = Localizer::new(language);
+        let bundle = &localizer.bundles[0];
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        let pattern = bundle.get_message(id).unwrap().value().unwrap();
+        let mut errors = vec![];
+        let message = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+        assert!(errors.is_empty(), "{errors:?}");
+        message.into_owned()
+    }
+
+    #[test]
+    fn test_language_from_locale() {
+        assert_eq!(language_from_locale("de_CH.UTF-8"), "de");
+        assert_eq!(language_from_locale("fr_FR@euro"), "fr");
+        assert_eq!(language_from_locale("C"), "en");
+        assert_eq!(language_from_locale("POSIX"), "en");
+        assert_eq!(language_from_locale("ja_JP.UTF-8"), "en");
+    }
+
+    #[test]
+    fn test_catalogs_have_all_messages() {
+        let english_ids = CATALOGS[0]
+            .1
+            .lines()
+            .filter(|line| !line.starts_with(' '))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id));
+        let localizers = CATALOGS.map(|(language, _)| (language, Localizer::new(language)));
+        for id in english_ids {
+            for (language, localizer) in &localizers {
+                assert!(
+                    localizer.bundles[0].has_message(id),
+                    "{language} is missing {id}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_plurals() {
+        assert_eq!(
+            format("en", "ago-days", &[("count", 1.into())]),
+            "1 day ago"
+        );
+        assert_eq!(
+            format("en", "ago-days", &[("count", 5.into())]),
+            "5 days ago"
+        );
+        assert_eq!(
+            format("de", "ago-days", &[("count", 5.into())]),
+            "vor 5 Tagen"
+        );
+        assert_eq!(
+            format("fr", "ago-days", &[("count", 5.into())]),
+            "il y a 5 jours"
+        );
+    }
+
+    #[test]
+    fn test_default_is_english() {
+        assert_eq!(tr("summary-nothing"), "All good");
+        assert_eq!(
+            tr_args("acknowledged", &[("summary", "All good".into())]),
+            "Acknowledged: All good"
+        );
+    }
+}
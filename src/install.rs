@@ -0,0 +1,305 @@
+//! Generate, check and remove the pacman hooks and systemd user units.
+
+use crate::config::xdg_dir;
+use crate::i18n::tr_args;
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Where the binary gets installed by the package.
+pub const PACKAGED_BINARY_PATH: &str = "/usr/bin/reboot-arch-btw";
+pub const USER_SERVICE_NAME: &str = "reboot-arch-btw.service";
+pub const TIMER_NAME: &str = "reboot-arch-btw-check.timer";
+
+const GENERATED_HEADER: &str = "# Generated by reboot-arch-btw, changes get overwritten.\n";
+
+/// A generated file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Install,
+    /// Check whether the installed files match the generated ones.
+    Check,
+    Remove,
+}
+
+/// The user to notify from the pacman hook.
+#[derive(Debug, PartialEq, Eq)]
+pub struct User {
+    pub name: String,
+    pub uid: u32,
+}
+
+impl User {
+    /// Look up `name` in `<root>/etc/passwd`.
+    pub fn lookup(root: &Path, name: &str) -> Result<User> {
+        let passwd_path = root.join("etc/passwd");
+        let passwd = fs::read_to_string(&passwd_path)
+            .with_context(|| anyhow!("Could not read {}", passwd_path.display()))?;
+        Self::from_passwd(&passwd, name)
+            .ok_or_else(|| anyhow!("Could not find user {name} in {}", passwd_path.display()))
+    }
+
+    fn from_passwd(passwd: &str, name: &str) -> Option<User> {
+        passwd.lines().find_map(|line| {
+            let mut fields = line.split(':');
+            if fields.next()? != name {
+                return None;
+            }
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some(User {
+                name: name.to_owned(),
+                uid,
+            })
+        })
+    }
+}
+
+/// The pacman hooks checking after and warning before a transaction.
+///
+/// Without `user` the post transaction hook only prints the result, since notifications have to
+/// be sent from the session of the user.
+pub fn hook_files(root: &Path, binary: &str, user: Option<&User>) -> Vec<GeneratedFile> {
+    let hooks_dir = root.join("etc/pacman.d/hooks");
+    let exec = match user {
+        Some(User { name, uid }) => format!(
+            "/usr/bin/sudo -u {name} DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/{uid}/bus \
+             {binary} --action-timeout 0 --hook"
+        ),
+        None => format!("{binary} --disable-notification --hook"),
+    };
+    vec![
+        GeneratedFile {
+            path: hooks_dir.join("10-reboot-arch-btw-pre.hook"),
+            content: hook(
+                "Check whether the upgrade breaks loading kernel modules",
+                "PreTransaction",
+                &format!("{binary} --hook=pre"),
+                // Lets "--protect" abort the transaction.
+                "AbortOnFail\n",
+            ),
+        },
+        GeneratedFile {
+            path: hooks_dir.join("99-reboot-arch-btw.hook"),
+            content: hook(
+                "Check whether a reboot is required",
+                "PostTransaction",
+                &exec,
+                "",
+            ),
+        },
+    ]
+}
+
+/// `options` are added to the end of the `[Action]` section.
+fn hook(description: &str, when: &str, exec: &str, options: &str) -> String {
+    format!(
+        "{GENERATED_HEADER}\
+         [Trigger]\n\
+         Operation = Upgrade\n\
+         Type = Package\n\
+         Target = *\n\
+         \n\
+         [Action]\n\
+         Description = {description}\n\
+         Depends = reboot-arch-btw\n\
+         When = {when}\n\
+         Exec = {exec}\n\
+         NeedsTargets\n\
+         {options}"
+    )
+}
+
+/// Directory of the systemd user units.
+///
+/// With `root` they get installed for all users, otherwise for the current user only.
+pub fn user_unit_dir(root: Option<&Path>) -> Result<PathBuf> {
+    match root {
+        Some(root) => Ok(root.join("usr/lib/systemd/user")),
+        None => Ok(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("systemd/user")),
+    }
+}
+
+/// The user service running `watch`.
+pub fn user_service_files(unit_dir: &Path, binary: &str) -> Vec<GeneratedFile> {
+    vec![GeneratedFile {
+        path: unit_dir.join(USER_SERVICE_NAME),
+        content: format!(
+            "{GENERATED_HEADER}\
+             [Unit]\n\
+             Description=Check whether a reboot is required after pacman transactions\n\
+             \n\
+             [Service]\n\
+             ExecStart={binary} watch\n\
+             \n\
+             [Install]\n\
+             WantedBy=default.target\n"
+        ),
+    }]
+}
+
+/// A timer checking periodically, for those who don't want a running service.
+pub fn timer_files(unit_dir: &Path, binary: &str) -> Vec<GeneratedFile> {
+    vec![
+        GeneratedFile {
+            path: unit_dir.join("reboot-arch-btw-check.service"),
+            content: format!(
+                "{GENERATED_HEADER}\
+                 [Unit]\n\
+                 Description=Check whether a reboot is required\n\
+                 \n\
+                 [Service]\n\
+                 Type=oneshot\n\
+                 ExecStart={binary}\n"
+            ),
+        },
+        GeneratedFile {
+            path: unit_dir.join(TIMER_NAME),
+            content: format!(
+                "{GENERATED_HEADER}\
+                 [Unit]\n\
+                 Description=Check periodically whether a reboot is required\n\
+                 \n\
+                 [Timer]\n\
+                 OnStartupSec=5min\n\
+                 OnUnitActiveSec=1h\n\
+                 \n\
+                 [Install]\n\
+                 WantedBy=timers.target\n"
+            ),
+        },
+    ]
+}
+
+/// Install, check or remove `files`.
+///
+/// Files which weren't generated by us, e.g. hand written hooks at the same path, are left alone.
+/// Returns `false` if there was such a file or checking found a missing or outdated file.
+pub fn apply(files: &[GeneratedFile], operation: Operation) -> Result<bool> {
+    let mut up_to_date = true;
+    for file in files {
+        let path = file.path.display().to_string();
+        let existing = match fs::read_to_string(&file.path) {
+            Ok(content) => Some(content),
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => return Err(err).with_context(|| anyhow!("Could not read {path}")),
+        };
+        if existing
+            .as_ref()
+            .is_some_and(|content| !content.starts_with(GENERATED_HEADER))
+        {
+            println!(
+                "{}",
+                tr_args("install-not-managed", &[("path", path.into())])
+            );
+            up_to_date = false;
+            continue;
+        }
+        match operation {
+            Operation::Install => {
+                if let Some(dir) = file.path.parent() {
+                    fs::create_dir_all(dir)
+                        .with_context(|| anyhow!("Could not create {}", dir.display()))?;
+                }
+                fs::write(&file.path, &file.content)
+                    .with_context(|| anyhow!("Could not write {path}"))?;
+                println!("{}", tr_args("install-installed", &[("path", path.into())]));
+            }
+            Operation::Check => {
+                let id = match existing {
+                    Some(content) if content == file.content => "install-up-to-date",
+                    Some(_) => "install-outdated",
+                    None => "install-missing",
+                };
+                up_to_date &= id == "install-up-to-date";
+                println!("{}", tr_args(id, &[("path", path.into())]));
+            }
+            Operation::Remove => {
+                if existing.is_some() {
+                    fs::remove_file(&file.path)
+                        .with_context(|| anyhow!("Could not remove {path}"))?;
+                    println!("{}", tr_args("install-removed", &[("path", path.into())]));
+                }
+            }
+        }
+    }
+    Ok(up_to_date)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn test_user_from_passwd() {
+        let passwd = "root:x:0:0::/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/zsh\n";
+        assert_eq!(
+            User::from_passwd(passwd, "alice"),
+            Some(User {
+                name: "alice".to_owned(),
+                uid: 1000
+            })
+        );
+        assert_eq!(User::from_passwd(passwd, "bob"), None);
+    }
+
+    #[test]
+    fn test_hook_with_user() {
+        let user = User {
+            name: "alice".to_owned(),
+            uid: 1000,
+        };
+        let files = hook_files(Path::new("/tmp/root"), PACKAGED_BINARY_PATH, Some(&user));
+        assert_eq!(
+            files[1].path,
+            Path::new("/tmp/root/etc/pacman.d/hooks/99-reboot-arch-btw.hook")
+        );
+        assert!(files[1].content.contains(
+            "Exec = /usr/bin/sudo -u alice DBUS_SESSION_BUS_ADDRESS=unix:path=/run/user/1000/bus \
+             /usr/bin/reboot-arch-btw --action-timeout 0 --hook\nNeedsTargets\n"
+        ));
+        assert!(files[0].content.contains("When = PreTransaction\n"));
+        assert!(files[0].content.ends_with("NeedsTargets\nAbortOnFail\n"));
+        assert!(!files[1].content.contains("AbortOnFail"));
+    }
+
+    #[test]
+    fn test_hook_without_user() {
+        let files = hook_files(Path::new("/"), PACKAGED_BINARY_PATH, None);
+        assert!(
+            files[1]
+                .content
+                .contains("Exec = /usr/bin/reboot-arch-btw --disable-notification --hook\n")
+        );
+    }
+
+    #[test]
+    fn test_install_check_remove() {
+        let dir = TestDir::new("install");
+        let files = timer_files(dir.path(), PACKAGED_BINARY_PATH);
+
+        assert!(!apply(&files, Operation::Check).unwrap());
+        assert!(apply(&files, Operation::Install).unwrap());
+        assert!(apply(&files, Operation::Check).unwrap());
+        fs::write(&files[0].path, format!("{GENERATED_HEADER}changed")).unwrap();
+        assert!(!apply(&files, Operation::Check).unwrap());
+        assert!(apply(&files, Operation::Remove).unwrap());
+        assert!(!files[0].path.exists() && !files[1].path.exists());
+        // Removing again is fine.
+        assert!(apply(&files, Operation::Remove).unwrap());
+
+        // Hand written files are left alone.
+        fs::write(&files[0].path, "[Unit]\n").unwrap();
+        assert!(!apply(&files, Operation::Install).unwrap());
+        assert!(!apply(&files, Operation::Remove).unwrap());
+        assert_eq!(fs::read_to_string(&files[0].path).unwrap(), "[Unit]\n");
+        assert!(!files[1].path.exists());
+    }
+}
@@ -0,0 +1,182 @@
+//! Logging to the systemd journal with structured fields.
+//!
+//! Uses the native journal protocol: every entry is a datagram of `FIELD=value` lines sent to the
+//! journal socket, see `systemd.journal-fields(7)` and
+//! <https://systemd.io/JOURNAL_NATIVE_PROTOCOL/>.
+
+use crate::checks::{CheckReport, CheckResult};
+use anyhow::{Context, Result, anyhow};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::env;
+use std::os::unix::net::UnixDatagram;
+
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+const SYSLOG_IDENTIFIER: &str = "reboot-arch-btw";
+
+/// Identifies the entries about a changed result, see `catalog/reboot-arch-btw.catalog`.
+pub const RESULT_CHANGED_MESSAGE_ID: &str = "ed89c7bb4ed049899cdee1309ccded29";
+
+/// Send an entry with the given fields to the journal.
+pub fn send(fields: &[(&str, String)]) -> Result<()> {
+    let socket = UnixDatagram::unbound().context("Could not create socket")?;
+    socket
+        .send_to(&encode(fields), JOURNAL_SOCKET)
+        .with_context(|| anyhow!("Could not send to {JOURNAL_SOCKET}"))?;
+    Ok(())
+}
+
+fn encode(fields: &[(&str, String)]) -> Vec<u8> {
+    let mut data = vec![];
+    for (name, value) in fields {
+        data.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            // Values with newlines are sent with their length instead.
+            data.push(b'\n');
+            data.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            data.push(b'=');
+        }
+        data.extend_from_slice(value.as_bytes());
+        data.push(b'\n');
+    }
+    data
+}
+
+/// The syslog priority of a log level.
+fn priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+struct JournalLogger {
+    level: LevelFilter,
+}
+
+impl Log for JournalLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut fields = vec![
+            ("MESSAGE", record.args().to_string()),
+            ("PRIORITY", priority(record.level()).to_string()),
+            ("SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER.to_owned()),
+            ("TARGET", record.target().to_owned()),
+        ];
+        if let Some(file) = record.file() {
+            fields.push(("CODE_FILE", file.to_owned()));
+        }
+        if let Some(line) = record.line() {
+            fields.push(("CODE_LINE", line.to_string()));
+        }
+        // There is nowhere else to report to.
+        send(&fields).ok();
+    }
+
+    fn flush(&self) {}
+}
+
+/// Use the journal for all log messages, up to the level in `RUST_LOG` or warnings by default.
+pub fn init() {
+    let level = env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(LevelFilter::Warn);
+    log::set_boxed_logger(Box::new(JournalLogger { level })).expect("logger is only set once");
+    log::set_max_level(level);
+}
+
+/// The fields of the entry about a changed result.
+pub fn report_fields(report: &CheckReport, summary: &str) -> Vec<(&'static str, String)> {
+    let priority = match report.result {
+        CheckResult::Nothing => 6,
+        CheckResult::RestartSession | CheckResult::Reboot => 5,
+        CheckResult::KernelUpdate => 4,
+    };
+    let mut fields = vec![
+        ("MESSAGE", summary.to_owned()),
+        ("MESSAGE_ID", RESULT_CHANGED_MESSAGE_ID.to_owned()),
+        ("PRIORITY", priority.to_string()),
+        ("SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER.to_owned()),
+        ("REBOOT_ARCH_BTW_RESULT", report.result.name().to_owned()),
+    ];
+    if let Some(kernel) = &report.kernel {
+        fields.push(("REBOOT_ARCH_BTW_RUNNING_KERNEL", kernel.running.clone()));
+        fields.push((
+            "REBOOT_ARCH_BTW_INSTALLED_KERNEL",
+            kernel.installed.version.clone(),
+        ));
+    }
+    // The journal allows a field to be given multiple times.
+    for package in &report.packages {
+        fields.push((
+            "REBOOT_ARCH_BTW_PACKAGE",
+            format!("{} {}", package.name, package.info.version),
+        ));
+    }
+    fields
+}
+
+/// Send the entry about a changed result.
+pub fn log_report(report: &CheckReport) -> Result<()> {
+    send(&report_fields(report, &report.result.summary()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::{KernelVersions, triggering_package};
+    use crate::package::PackageInfo;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            encode(&[
+                ("MESSAGE", "Reboot arch btw".to_owned()),
+                ("PRIORITY", "5".to_owned())
+            ]),
+            b"MESSAGE=Reboot arch btw\nPRIORITY=5\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_newline() {
+        assert_eq!(
+            encode(&[("MESSAGE", "a\nb".to_owned())]),
+            b"MESSAGE\n\x03\0\0\0\0\0\0\0a\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_report_fields() {
+        let mut report = CheckReport {
+            kernel: Some(KernelVersions {
+                running: "6.10.5.arch1.1".to_owned(),
+                installed: PackageInfo {
+                    version: "6.10.6.arch1.1".to_owned(),
+                    install_date: None,
+                },
+            }),
+            ..CheckReport::default()
+        };
+        let mut linux = triggering_package("linux", CheckResult::KernelUpdate);
+        linux.info.version = "6.10.6.arch1-1".to_owned();
+        report.add_package(linux);
+        let fields = report_fields(&report, "Reboot arch btw");
+        assert!(fields.contains(&("REBOOT_ARCH_BTW_RESULT", "kernel-update".to_owned())));
+        assert!(fields.contains(&("PRIORITY", "4".to_owned())));
+        assert!(fields.contains(&(
+            "REBOOT_ARCH_BTW_RUNNING_KERNEL",
+            "6.10.5.arch1.1".to_owned()
+        )));
+        assert!(fields.contains(&("REBOOT_ARCH_BTW_PACKAGE", "linux 6.10.6.arch1-1".to_owned())));
+    }
+}
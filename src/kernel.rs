@@ -1,4 +1,5 @@
-use crate::checks::{Check, CheckResult};
+use crate::checks::{Check, CheckReport, CheckResult, KernelVersions, TriggeringPackage};
+use crate::i18n::{tr, tr_args};
 use crate::package::{PackageInfo, get_package_version};
 use anyhow::{Context, Result, anyhow};
 use log::info;
@@ -22,6 +23,14 @@ impl Display for KernelInfo {
     }
 }
 
+/// The release of the running kernel as printed by `uname -r`, e.g. "6.10.6-arch1-1".
+pub fn uname_release() -> Result<String> {
+    let output_uname = Command::new("uname").arg("-r").output()?;
+    Ok(String::from_utf8_lossy(&output_uname.stdout)
+        .trim()
+        .to_owned())
+}
+
 /// These variants trip up our auto-detection since they contain multiple dashes and numbers
 const WELL_KNOWN_VARIANTS: [&str; 4] = [
     "ck-generic",
@@ -32,9 +41,7 @@ const WELL_KNOWN_VARIANTS: [&str; 4] = [
 
 impl KernelInfo {
     pub fn from_uname() -> Result<KernelInfo> {
-        let output_uname = Command::new("uname").arg("-r").output()?;
-        let output_uname_stdout = String::from_utf8_lossy(&output_uname.stdout);
-        Self::from_uname_output(&output_uname_stdout)
+        Self::from_uname_output(&uname_release()?)
     }
     pub fn from_uname_output(uname_output: &str) -> Result<KernelInfo> {
         // uname output is in the form version-ARCH
@@ -119,29 +126,60 @@ impl KernelChecker {
             verbose,
         })
     }
+
+    /// The name of the package of the running kernel.
+    pub fn package_name(&self) -> &str {
+        &self.kernel_info.package_name
+    }
 }
 
 impl Check for KernelChecker {
-    fn check(&self) -> CheckResult {
+    fn check(&self) -> CheckReport {
         let cleaned_kernel_version =
             PackageInfo::cleanup_kernel_version(&self.installed_kernel.version)
                 .expect("Could not clean version of installed kernel");
         let running_kernel_version = &self.kernel_info.version;
         let should_reboot = running_kernel_version != &cleaned_kernel_version;
         if self.verbose {
-            println!("Kernel");
+            println!("{}", tr("verbose-kernel"));
             println!(
-                " installed: {} (since {})",
-                cleaned_kernel_version,
-                self.installed_kernel.installed_reltime()
+                " {}",
+                tr_args(
+                    "verbose-kernel-installed",
+                    &[
+                        ("version", cleaned_kernel_version.as_str().into()),
+                        ("ago", self.installed_kernel.installed_reltime().into()),
+                    ],
+                )
+            );
+            println!(
+                " {}",
+                tr_args(
+                    "verbose-kernel-running",
+                    &[("version", self.kernel_info.to_string().into())],
+                )
             );
-            println!(" running:   {}", self.kernel_info);
         }
+        let mut report = CheckReport {
+            kernel: Some(KernelVersions {
+                running: running_kernel_version.clone(),
+                installed: PackageInfo {
+                    version: cleaned_kernel_version,
+                    install_date: self.installed_kernel.install_date,
+                },
+            }),
+            ..CheckReport::default()
+        };
         if should_reboot {
-            CheckResult::KernelUpdate
-        } else {
-            CheckResult::Nothing
+            report.add_package(TriggeringPackage {
+                name: self.kernel_info.package_name.clone(),
+                info: self.installed_kernel.clone(),
+                // The running kernel is shown from `kernel` instead.
+                previous_version: None,
+                result: CheckResult::KernelUpdate,
+            });
         }
+        report
     }
 }
 
@@ -237,7 +275,9 @@ mod test {
             verbose: false,
         };
 
-        assert_eq!(kernel_checker.check(), CheckResult::KernelUpdate);
+        let report = kernel_checker.check();
+        assert_eq!(report.result, CheckResult::KernelUpdate);
+        assert_eq!(report.packages[0].name, "linux");
     }
 
     #[test]
@@ -251,6 +291,8 @@ mod test {
             verbose: false,
         };
 
-        assert_eq!(kernel_checker.check(), CheckResult::Nothing);
+        let report = kernel_checker.check();
+        assert_eq!(report.result, CheckResult::Nothing);
+        assert!(report.packages.is_empty());
     }
 }
@@ -0,0 +1,31 @@
+use anyhow::{Context, Result};
+use dbus::blocking::Connection;
+use std::time::Duration;
+
+const LOGIND_BUS_NAME: &str = "org.freedesktop.login1";
+const DBUS_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// Ask logind to reboot the system.
+///
+/// The call is interactive, so polkit may ask the user to authenticate.
+pub fn reboot() -> Result<()> {
+    let connection = Connection::new_system().context("Could not connect to the system bus")?;
+    let proxy = connection.with_proxy(LOGIND_BUS_NAME, "/org/freedesktop/login1", DBUS_TIMEOUT);
+    proxy
+        .method_call::<(), _, _, _>("org.freedesktop.login1.Manager", "Reboot", (true,))
+        .context("Could not reboot via logind")
+}
+
+/// Ask logind to terminate the session this process belongs to.
+pub fn terminate_session() -> Result<()> {
+    let connection = Connection::new_system().context("Could not connect to the system bus")?;
+    // logind resolves the "auto" session to the session of the caller.
+    let proxy = connection.with_proxy(
+        LOGIND_BUS_NAME,
+        "/org/freedesktop/login1/session/auto",
+        DBUS_TIMEOUT,
+    );
+    proxy
+        .method_call::<(), _, _, _>("org.freedesktop.login1.Session", "Terminate", ())
+        .context("Could not terminate session via logind")
+}
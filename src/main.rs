@@ -1,17 +1,72 @@
-use clap::Parser;
-use log::error;
-use notify_rust::{Notification, Timeout};
+use anyhow::{Context, Result, anyhow};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use log::{error, warn};
+use notify_rust::Timeout;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use time::OffsetDateTime;
 
 mod package;
+mod package_list;
+use package_list::PackageRule;
+mod pacman_log;
+mod preset;
+mod preview;
+mod prometheus;
+use pacman_log::PacmanLog;
+use preset::SessionPreset;
+use preview::PreviewCheck;
 
 mod kernel;
-use kernel::KernelChecker;
+use kernel::{KernelChecker, KernelInfo};
 
+mod cache;
+use cache::CacheKey;
 mod checks;
-use checks::{Check, CheckResult};
+use checks::{Check, CheckReport, CheckResult};
 mod critical_packages_check;
 use critical_packages_check::CriticalPackagesCheck;
+mod config;
+use config::Config;
+mod db_lock;
+mod duration;
+mod explain;
+use duration::format_duration;
+mod hook;
+use hook::{HookMode, ModuleBreakage};
+mod history;
+mod i18n;
+mod install;
+mod journal;
+use i18n::{tr, tr_args};
+use install::Operation;
+mod logind;
+mod monitoring;
+mod motd;
+use monitoring::{Monitoring, PerfData, Status};
+mod notification;
+mod reboot_required;
 mod session;
+use notification::{Action, Notifier};
+use session::SessionInfo;
+mod state;
+use state::State;
+mod status_bar;
+use status_bar::Format;
+mod template;
+#[cfg(test)]
+mod test_dir;
+mod version;
+use template::Message;
+mod watch;
+
+const DEFAULT_ACTION_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_DB_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+/// NRPE and Checkmk agents usually give up on a check after 10s.
+const MONITORING_DB_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Parser)]
 #[clap(
@@ -19,6 +74,15 @@ mod session;
     about = "Check if a reboot is needed due to an updated kernel or other system packages."
 )]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the config file.
+    ///
+    /// Defaults to "$XDG_CONFIG_HOME/reboot-arch-btw/config.toml".
+    #[clap(long)]
+    config: Option<PathBuf>,
+
     /// Disable desktop notification
     #[clap(long)]
     disable_notification: bool,
@@ -33,7 +97,103 @@ struct Args {
     #[clap(long, default_value = "default")]
     notification_timeout: Timeout,
 
+    /// How long to wait for a click on one of the notification buttons.
+    ///
+    /// The buttons offer to reboot, to log out or to remind again later. Use "0" to show the
+    /// notification without buttons and exit right away. Defaults to 60s when running in a user
+    /// session and to 0 otherwise, e.g. in pacman hooks.
+    #[clap(long, value_parser = duration::parse_duration)]
+    action_timeout: Option<Duration>,
+
+    /// Snooze notifications for this long when "Remind me later" was clicked.
+    #[clap(long, default_value = "1h", value_parser = duration::parse_duration)]
+    remind_after: Duration,
+
+    /// Don't notify about the current result for the given duration, e.g. "30m", "2h" or "1d".
+    ///
+    /// The snooze ends early if something new gets updated.
+    #[clap(long, value_parser = duration::parse_duration)]
+    snooze: Option<Duration>,
+
+    /// How long to wait for a running pacman transaction to finish before giving up.
+    ///
+    /// Defaults to 30s and to 5s with "--monitoring", since monitoring agents usually give up on
+    /// a check after 10s.
+    #[clap(long, value_parser = duration::parse_duration)]
+    db_lock_timeout: Option<Duration>,
+
+    /// Check even if the pacman database is locked.
+    ///
+    /// This is implied when running below pacman, e.g. from a pacman hook, since pacman holds the
+    /// lock while running hooks.
+    #[clap(long)]
+    ignore_db_lock: bool,
+
+    /// Run as pacman hook with `NeedsTargets`.
+    ///
+    /// Reads the package names of the transaction from stdin. "post" only checks those packages,
+    /// "pre" warns if they include the running kernel or modules for it. Implies
+    /// `--ignore-db-lock`.
+    #[clap(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "post")]
+    hook: Option<HookMode>,
+
+    /// Abort the transaction with "--hook=pre" if it would upgrade the running kernel or
+    /// modules for it.
+    ///
+    /// Needs `AbortOnFail` in the hook.
+    #[clap(long, value_enum, use_value_delimiter = true)]
+    protect: Vec<ModuleBreakage>,
+
+    /// Write Debian compatible /run/reboot-required and /run/reboot-required.pkgs files if a
+    /// reboot is required and remove them otherwise.
+    ///
+    /// Needs write access to /run, so usually root.
+    #[clap(long)]
+    write_reboot_required: bool,
+
+    /// Write the result to /run/motd.d/ to show it on login, e.g. via SSH.
+    ///
+    /// Needs write access to /run/motd.d, so usually root.
+    #[clap(long)]
+    motd: bool,
+
+    /// Log to the systemd journal instead of stderr.
+    ///
+    /// Changes of the result and findings of "--hook" get logged with structured fields like
+    /// REBOOT_ARCH_BTW_RESULT and the message ID ed89c7bb4ed049899cdee1309ccded29. For
+    /// explanations with "journalctl -x" install catalog/reboot-arch-btw.catalog to
+    /// /usr/lib/systemd/catalog/ and run "journalctl --update-catalog".
+    #[clap(long)]
+    journal: bool,
+
+    /// Print the result for a monitoring system instead of notifying.
+    ///
+    /// Nothing is OK, a session restart or reboot WARNING and a kernel update CRITICAL. A running
+    /// pacman transaction or a failed check is UNKNOWN.
+    #[clap(long, value_enum)]
+    monitoring: Option<Monitoring>,
+
+    /// Write metrics for the textfile collector of the Prometheus node exporter to this file.
+    ///
+    /// The file gets replaced atomically. Ignored with "--hook", since only some packages get
+    /// checked then.
+    #[clap(long)]
+    prometheus_textfile: Option<PathBuf>,
+
+    /// Print the result for a status bar instead of notifying.
+    ///
+    /// "waybar" prints JSON for a custom module with "return-type": "json", with the CSS class
+    /// being the result, e.g. "reboot".
+    #[clap(long, value_enum, conflicts_with = "monitoring")]
+    format: Option<Format>,
+
     /// Comma separated list of packages where we should reboot after an upgrade.
+    ///
+    /// Accepts globs like "nvidia*", groups like "@plasma", regular expressions like
+    /// "/linux-firmware-.*/" and names provided by other packages.
+    ///
+    /// Append the change which triggers a package, like "systemd:minor", to ignore smaller
+    /// updates: epoch, major, minor, pkgver or pkgrel (the default, any change).
     #[clap(
         long,
         use_value_delimiter = true,
@@ -42,6 +202,8 @@ struct Args {
     reboot_packages: Vec<String>,
 
     /// Comma separated list of packages where we should restart our session after an upgrade.
+    ///
+    /// Accepts the same patterns as "--reboot-packages".
     #[clap(
         long,
         use_value_delimiter = true,
@@ -49,57 +211,827 @@ struct Args {
     )]
     session_restart_packages: Vec<String>,
 
+    /// Comma separated list of packages whose runtime dependencies all require a session restart
+    /// after an upgrade, e.g. the compositor or desktop like "sway" or "plasma-workspace".
+    ///
+    /// The dependencies get resolved with the local pacman database on every check, so updates of
+    /// e.g. mesa or Qt are covered without listing them. Accepts the same patterns as
+    /// "--reboot-packages".
+    #[clap(long, use_value_delimiter = true)]
+    session_dependency_packages: Vec<String>,
+
+    /// Add the session restart packages of a desktop environment to "--session-restart-packages".
+    ///
+    /// "auto" detects it from XDG_CURRENT_DESKTOP or XDG_SESSION_DESKTOP.
+    #[clap(long, value_enum)]
+    session_preset: Option<SessionPreset>,
+
     /// Print kernel version info and show updated packages.
     #[clap(short, long)]
     verbose: bool,
 }
 
+impl Args {
+    /// Only wait for a click by default if a user is around to click, so pacman doesn't block.
+    fn action_timeout(&self) -> Duration {
+        self.action_timeout.unwrap_or_else(|| {
+            if self.hook.is_none() && session::in_user_session() {
+                DEFAULT_ACTION_TIMEOUT
+            } else {
+                Duration::ZERO
+            }
+        })
+    }
+
+    /// Report UNKNOWN to monitoring systems before they time out the whole check.
+    fn db_lock_timeout(&self) -> Duration {
+        self.db_lock_timeout
+            .unwrap_or(if self.monitoring.is_some() {
+                MONITORING_DB_LOCK_TIMEOUT
+            } else {
+                DEFAULT_DB_LOCK_TIMEOUT
+            })
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Acknowledge the current result and don't notify about it again until something changes.
+    Ack,
+    /// Keep running and check again after every pacman transaction.
+    ///
+    /// This is meant to be run as systemd user service. It watches the pacman database with
+    /// inotify, so it also notices updates done by AUR helpers or unattended jobs.
+    Watch,
+    /// Show whether upgrading now would require a reboot or a new session.
+    ///
+    /// Uses the sync databases on disk without downloading anything, so the answer is only as
+    /// recent as the last database refresh.
+    Preview,
+    /// Show when reboots were required and how long it took until they happened.
+    History,
+    /// List all packages installed since boot and since the session started.
+    ///
+    /// Shows which list each package is in and marks unlisted packages which ship libraries or
+    /// system services, to help tuning "--reboot-packages" and "--session-restart-packages".
+    Explain,
+    /// Print the result in a single line without notifying, or nothing if all is good.
+    ///
+    /// Meant for shell prompts. Every check updates the cache used by "--cached".
+    Status {
+        /// Use the result of the last check, unless packages got updated, someone logged in or
+        /// the system got rebooted since.
+        ///
+        /// Never waits for a running pacman transaction, but prints the outdated result instead.
+        #[clap(long)]
+        cached: bool,
+    },
+    /// Install the pacman hooks, usually with sudo.
+    ///
+    /// The hook run after a transaction notifies the user who ran sudo or the one given with
+    /// "--user". Without user it only prints the result.
+    InstallHook {
+        #[clap(flatten)]
+        install: InstallArgs,
+
+        /// Name of the user to notify.
+        #[clap(long)]
+        user: Option<String>,
+    },
+    /// Install a systemd user service which runs "watch".
+    InstallUserService(InstallArgs),
+    /// Install a systemd user timer which checks once an hour.
+    InstallTimer(InstallArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct InstallArgs {
+    /// Install below this directory, e.g. when packaging.
+    ///
+    /// systemd user units get installed for all users then.
+    #[clap(long)]
+    root: Option<PathBuf>,
+
+    /// Check whether the installed files are up to date instead of installing them.
+    #[clap(long, conflicts_with = "remove")]
+    check: bool,
+
+    /// Remove the installed files.
+    #[clap(long)]
+    remove: bool,
+}
+
+impl InstallArgs {
+    fn operation(&self) -> Operation {
+        if self.check {
+            Operation::Check
+        } else if self.remove {
+            Operation::Remove
+        } else {
+            Operation::Install
+        }
+    }
+
+    /// Path of the binary to run from the generated files.
+    fn binary(&self) -> Result<String> {
+        match self.root {
+            Some(_) => Ok(install::PACKAGED_BINARY_PATH.to_owned()),
+            None => Ok(env::current_exe()?.display().to_string()),
+        }
+    }
+}
+
+fn print_details(report: &CheckReport) {
+    for line in report.details() {
+        println!(" {line}");
+    }
+}
+
 fn main() {
-    env_logger::init();
-    let args = Args::parse();
+    let mut args = Args::parse();
+    // Clap can't declare conflicts with subcommands. Output formats and hooks would exit after the
+    // first check, and a snooze doesn't belong to a single check.
+    if matches!(args.command, Some(Command::Watch))
+        && (args.format.is_some()
+            || args.monitoring.is_some()
+            || args.hook.is_some()
+            || args.snooze.is_some())
+    {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "watch can't be combined with --format, --monitoring, --hook or --snooze",
+            )
+            .exit();
+    }
+    if args.journal {
+        journal::init();
+    } else {
+        env_logger::init();
+    }
+    if let Some(preset) = args.session_preset.and_then(SessionPreset::resolve) {
+        args.session_restart_packages
+            .extend(preset.packages().iter().map(|name| name.to_string()));
+    }
+    i18n::init();
+    let config = Config::load(args.config.as_deref()).unwrap_or_else(|err| {
+        error!("Could not load config: {err:#}");
+        Config::default()
+    });
+
+    match args.command {
+        Some(Command::Watch) => {
+            if let Err(err) = watch::watch(|| check_and_report(&args, &config)) {
+                error!("Could not watch pacman database: {err:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Preview) => preview(&args),
+        Some(Command::History) => print_history().unwrap_or_else(|err| {
+            error!("{err:#}");
+            std::process::exit(1);
+        }),
+        Some(Command::Explain) => explain(&args).unwrap_or_else(|err| {
+            error!("{err:#}");
+            std::process::exit(1);
+        }),
+        Some(
+            ref command @ (Command::InstallHook { .. }
+            | Command::InstallUserService(_)
+            | Command::InstallTimer(_)),
+        ) => match install(command) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                error!("{err:#}");
+                std::process::exit(1);
+            }
+        },
+        _ => check_and_report(&args, &config),
+    }
+}
+
+fn check_and_report(args: &Args, config: &Config) {
+    // Determined before checking, so changes while checking invalidate the cache.
+    let cache_key = if args.hook.is_none() {
+        CacheKey::current(
+            &args.reboot_packages,
+            &args.session_restart_packages,
+            &args.session_dependency_packages,
+        )
+        .inspect_err(|err| warn!("Could not determine cache key: {err:#}"))
+        .ok()
+    } else {
+        None
+    };
+    let cached = matches!(args.command, Some(Command::Status { cached: true }));
+    if cached
+        && let Some(key) = &cache_key
+        && let Some(report) = cache::path()
+            .ok()
+            .and_then(|path| cache::load_valid(&path, key))
+    {
+        print_short_status(args, &report);
+        return;
+    }
+    let targets = if args.hook.is_some() {
+        match hook::read_targets(io::stdin().lock()) {
+            Ok(targets) => Some(targets),
+            Err(err) => {
+                error!("{err:#}");
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    if let (Some(HookMode::Pre), Some(targets)) = (args.hook, &targets) {
+        if pre_transaction_hook(args, targets) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    // Shell prompts must not hang while pacman runs.
+    let db_lock_timeout = if cached {
+        Duration::ZERO
+    } else {
+        args.db_lock_timeout()
+    };
+    if !args.ignore_db_lock
+        && args.hook.is_none()
+        && !db_lock::pacman_is_ancestor()
+        && !db_lock::wait_for_unlock(Path::new(db_lock::PACMAN_DB_LOCK_PATH), db_lock_timeout)
+    {
+        if cached {
+            // The outdated result is better than none, the next prompt after the transaction
+            // updates it.
+            if let Some(cache) = cache::path().ok().and_then(|path| cache::load(&path)) {
+                print_short_status(args, &cache.report);
+            }
+            return;
+        }
+        // The local database might be half updated, so any result could be wrong.
+        let message = tr("transaction-in-progress");
+        if let Some(monitoring) = args.monitoring {
+            report_unknown(monitoring, &message);
+        }
+        if let Some(format) = args.format {
+            print_status(format, &status_bar::Status::transaction_in_progress());
+        }
+        println!("{message}");
+        return;
+    }
+    let pacman_log = PacmanLog::read().unwrap_or_else(|err| {
+        warn!("Could not read pacman log: {err:#}");
+        PacmanLog::default()
+    });
+    let report = match run_checks(args, targets.as_deref(), &pacman_log) {
+        Ok(report) => report,
+        Err(err) => {
+            error!("{err:#}");
+            if let Some(monitoring) = args.monitoring {
+                report_unknown(monitoring, &format!("{err:#}"));
+            }
+            return;
+        }
+    };
+    if args.write_reboot_required {
+        // Hooks only check some packages, so they must not forget about earlier updates.
+        reboot_required::update(
+            Path::new(reboot_required::RUN_DIR),
+            &report,
+            targets.is_some(),
+        )
+        .unwrap_or_else(|err| error!("{err:#}"));
+    }
+    if let (Some(path), None) = (&args.prometheus_textfile, &targets) {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let pending_since = report.reboot_pending_since(&pacman_log, boot_time(now));
+        prometheus::write_textfile(path, &report, now, pending_since)
+            .unwrap_or_else(|err| error!("{err:#}"));
+    }
+    if targets.is_none() {
+        record_history(&report, &pacman_log);
+    }
+    if args.journal {
+        log_to_journal(&report, targets.is_some());
+    }
+    if let Some(key) = cache_key {
+        cache::path()
+            .and_then(|path| cache::store(&path, key, &report))
+            .unwrap_or_else(|err| warn!("Could not update cache: {err:#}"));
+    }
+    if args.motd && targets.is_none() {
+        motd::update(Path::new(motd::MOTD_PATH), &report).unwrap_or_else(|err| error!("{err:#}"));
+    }
+    if let Some(Command::Status { .. }) = args.command {
+        print_short_status(args, &report);
+        return;
+    }
+    if let Some(format) = args.format {
+        print_status(format, &status_bar::Status::for_report(&report));
+    }
+    if let Some(monitoring) = args.monitoring {
+        report_monitoring(monitoring, &report, &pacman_log);
+    }
+    report_result(args, config, &report, targets.is_some());
+}
+
+/// Append to the history if the result changed or a reboot resolved it.
+fn record_history(report: &CheckReport, pacman_log: &PacmanLog) {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let changes = history::path().and_then(|path| {
+        let boot_id = session::boot_id()?;
+        let boot_time = now - session::uptime()?.as_secs() as i64;
+        // Checks may run long after the upgrade, e.g. from a timer.
+        let time = report
+            .reboot_pending_since(pacman_log, Some(boot_time))
+            .map_or(now, |since| since.min(now));
+        history::append(
+            &path,
+            &history::changes(&path, report, &boot_id, boot_time, time)?,
+        )
+    });
+    changes.unwrap_or_else(|err| warn!("Could not update history: {err:#}"));
+}
+
+/// Log `report` to the journal if its result differs from the last one logged.
+///
+/// A `partial` report of a hook only covers the packages of the transaction, so whatever it
+/// finds is new.
+fn log_to_journal(report: &CheckReport, partial: bool) {
+    if partial {
+        if report.result > CheckResult::Nothing {
+            journal::log_report(report).unwrap_or_else(|err| warn!("{err:#}"));
+        }
+        return;
+    }
+    let mut state = State::load().unwrap_or_else(|err| {
+        warn!("Could not load state: {err:#}");
+        State::default()
+    });
+    if state.logged_result.unwrap_or_default() == report.result {
+        return;
+    }
+    match journal::log_report(report) {
+        Ok(()) => {
+            state.logged_result = Some(report.result);
+            state
+                .save()
+                .unwrap_or_else(|err| warn!("Could not save state: {err:#}"));
+        }
+        Err(err) => warn!("{err:#}"),
+    }
+}
+
+/// Print the pending periods in the history and how long kernel updates stayed unrebooted.
+fn print_history() -> Result<()> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let periods = history::pending_periods(&history::load(&history::path()?)?);
+    if periods.is_empty() {
+        println!("{}", tr("history-empty"));
+        return Ok(());
+    }
+    for period in &periods {
+        let id = match period.end {
+            Some(_) => "history-resolved",
+            None => "history-pending",
+        };
+        let duration = period.end.unwrap_or(now) - period.start;
+        println!(
+            "{}",
+            tr_args(
+                id,
+                &[
+                    ("start", history::format_time(period.start).into()),
+                    ("result", period.result.name().into()),
+                    ("duration", format_duration(duration as u64).into()),
+                    ("packages", period.packages.join(", ").into()),
+                ]
+            )
+        );
+    }
+
+    let durations = history::resolved_durations(&periods, CheckResult::KernelUpdate);
+    if let Some(max) = durations.iter().max() {
+        let average = durations.iter().sum::<i64>() / durations.len() as i64;
+        println!(
+            "{}",
+            tr_args(
+                "history-kernel-updates",
+                &[
+                    ("count", durations.len().into()),
+                    ("average", format_duration(average as u64).into()),
+                    ("max", format_duration(*max as u64).into()),
+                ]
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Print the result in a single line for `status`, or nothing if all is good.
+fn print_short_status(args: &Args, report: &CheckReport) {
+    if let Some(format) = args.format {
+        print_status(format, &status_bar::Status::for_report(report));
+    }
+    if report.result > CheckResult::Nothing {
+        println!("{}", report.result.summary());
+    }
+}
+
+/// Unix timestamp of the boot, given the current time `now`.
+fn boot_time(now: i64) -> Option<i64> {
+    session::uptime()
+        .map(|uptime| now - uptime.as_secs() as i64)
+        .inspect_err(|err| warn!("{err:#}"))
+        .ok()
+}
+
+/// Print the status for a status bar and exit with the matching code.
+fn print_status(format: Format, status: &status_bar::Status) -> ! {
+    println!("{}", format.render(status));
+    std::process::exit(format.exit_code(status));
+}
+
+/// Print an UNKNOWN status with `message` for a monitoring system and exit.
+fn report_unknown(monitoring: Monitoring, message: &str) -> ! {
+    println!("{}", monitoring.format(Status::Unknown, message, &[], None));
+    std::process::exit(monitoring.exit_code(Status::Unknown));
+}
+
+/// Print the result for a monitoring system and exit with the matching code.
+fn report_monitoring(monitoring: Monitoring, report: &CheckReport, pacman_log: &PacmanLog) -> ! {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let boot_time = boot_time(now);
+    let kernel_upgrades = report
+        .packages
+        .iter()
+        .find(|package| package.result == CheckResult::KernelUpdate)
+        .zip(boot_time)
+        .map_or(0, |(kernel, boot_time)| {
+            pacman_log.upgrades_since(&kernel.name, boot_time)
+        });
+    let status = Status::for_result(report.result);
+    let pending_since = report.reboot_pending_since(pacman_log, boot_time);
+    let perf_data = PerfData::new(report, now, pending_since, kernel_upgrades);
+    println!(
+        "{}",
+        monitoring.format(
+            status,
+            &report.result.summary(),
+            &report.details(),
+            Some(&perf_data)
+        )
+    );
+    std::process::exit(monitoring.exit_code(status));
+}
+
+/// Install, check or remove the files for one of the install commands.
+///
+/// Returns `false` if checking found a missing or outdated file.
+fn install(command: &Command) -> Result<bool> {
+    let (install_args, files, unit) = match command {
+        Command::InstallHook { install, user } => {
+            let root = install.root.as_deref().unwrap_or(Path::new("/"));
+            let user = match user.clone().or_else(|| env::var("SUDO_USER").ok()) {
+                Some(name) => Some(install::User::lookup(root, &name)?),
+                None => None,
+            };
+            let files = install::hook_files(root, &install.binary()?, user.as_ref());
+            (install, files, None)
+        }
+        Command::InstallUserService(install) => {
+            let unit_dir = install::user_unit_dir(install.root.as_deref())?;
+            let files = install::user_service_files(&unit_dir, &install.binary()?);
+            (install, files, Some(install::USER_SERVICE_NAME))
+        }
+        Command::InstallTimer(install) => {
+            let unit_dir = install::user_unit_dir(install.root.as_deref())?;
+            let files = install::timer_files(&unit_dir, &install.binary()?);
+            (install, files, Some(install::TIMER_NAME))
+        }
+        _ => unreachable!("not an install command"),
+    };
+    let operation = install_args.operation();
+    if let (Operation::Remove, Some(unit)) = (operation, unit)
+        && files.iter().any(|file| file.path.exists())
+    {
+        disable_unit(unit, install_args.root.is_some());
+    }
+    let up_to_date = install::apply(&files, operation)?;
+    if let (Operation::Install, Some(unit)) = (operation, unit) {
+        let command = format!("systemctl --user enable --now {unit}");
+        println!(
+            "{}",
+            tr_args("install-enable", &[("command", command.into())])
+        );
+    }
+    Ok(up_to_date)
+}
+
+/// Disable `unit` before its files get removed, so no dangling `*.wants/` links stay behind.
+///
+/// Units installed for all users with `root` can only be disabled by everyone who enabled them.
+fn disable_unit(unit: &str, for_all_users: bool) {
+    let command = format!("systemctl --user disable --now {unit}");
+    if for_all_users {
+        println!(
+            "{}",
+            tr_args("install-disable", &[("command", command.into())])
+        );
+        return;
+    }
+    let status = std::process::Command::new("systemctl")
+        .args(["--user", "disable", "--now", unit])
+        .status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => warn!("{command} failed with {status}"),
+        Err(err) => warn!("Could not run {command}: {err}"),
+    }
+}
+
+/// Print the packages installed since the session started and since boot with their rules.
+fn explain(args: &Args) -> Result<()> {
+    let alpm = open_pacman_db()?;
+    let kernel_package = running_kernel_package();
+    let (reboot_packages, restart_session_packages) = package_lists(args, alpm.localdb());
+    let names = |rules: Vec<PackageRule>| rules.into_iter().map(|rule| rule.name).collect();
+    let rules = explain::Rules {
+        kernel_package,
+        reboot_package_names: names(reboot_packages),
+        restart_session_package_names: names(restart_session_packages),
+    };
+    let explanation = explain::explain(alpm.localdb(), SessionInfo::from_utmp()?, &rules);
+
+    if explanation.since_session.is_empty() && explanation.before_session.is_empty() {
+        println!("{}", tr("explain-nothing"));
+        return Ok(());
+    }
+    for (id, packages) in [
+        ("explain-since-session", &explanation.since_session),
+        ("explain-before-session", &explanation.before_session),
+    ] {
+        if packages.is_empty() {
+            continue;
+        }
+        println!("{}", tr(id));
+        for package in packages {
+            let mut line = format!(
+                " {} {} ({}): {}",
+                package.name,
+                package.info.version,
+                package.info.installed_reltime(),
+                tr_args(
+                    "explain-result",
+                    &[("result", package.result.name().into())]
+                )
+            );
+            if let Some(relevance) = package.relevance {
+                line += &format!(
+                    ", {}",
+                    tr_args(
+                        "explain-relevance",
+                        &[("relevance", relevance.name().into())]
+                    )
+                );
+            }
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+/// Print what upgrading with the sync databases on disk would require.
+fn preview(args: &Args) {
+    let alpm = open_pacman_db()
+        .and_then(|alpm| {
+            preview::register_sync_dbs(&alpm, Path::new(preview::PACMAN_CONF_PATH))?;
+            Ok(alpm)
+        })
+        .unwrap_or_else(|err| {
+            error!("{err:#}");
+            std::process::exit(1);
+        });
+    let kernel_package = running_kernel_package();
+    let (reboot_packages, restart_session_packages) = package_lists(args, alpm.localdb());
+    let check = PreviewCheck {
+        upgrades: preview::pending_upgrades(&alpm),
+        kernel_package,
+        reboot_packages,
+        restart_session_packages,
+    };
+    let report = check.check();
+
+    println!(
+        "{}",
+        tr_args("preview", &[("summary", report.result.summary().into())])
+    );
+    for package in &report.packages {
+        println!(
+            " {} {} → {}",
+            package.name,
+            package.previous_version.as_deref().unwrap_or_default(),
+            package.info.version
+        );
+    }
+}
+
+/// Warn about pending upgrades which break loading kernel modules until the next reboot.
+///
+/// Returns whether the transaction should be aborted because of `--protect`.
+fn pre_transaction_hook(args: &Args, targets: &[String]) -> bool {
+    let alpm = match open_pacman_db() {
+        Ok(alpm) => alpm,
+        Err(err) => {
+            error!("{err:#}");
+            return false;
+        }
+    };
+    let (kernel_info, kernel_release) = match kernel::uname_release()
+        .and_then(|release| Ok((KernelInfo::from_uname_output(&release)?, release)))
+    {
+        Ok(kernel) => kernel,
+        Err(err) => {
+            error!("Could not detect running kernel: {err:#}");
+            return false;
+        }
+    };
+    let breakages = hook::pending_breakages(
+        alpm.localdb(),
+        &kernel_info.package_name,
+        &kernel_release,
+        targets,
+    );
+
+    let mut abort = false;
+    for (breakage, package) in breakages {
+        let id = match breakage {
+            ModuleBreakage::Kernel => "pre-hook-kernel",
+            ModuleBreakage::Modules => "pre-hook-modules",
+        };
+        println!("{}", tr_args(id, &[("package", package.as_str().into())]));
+        if args.protect.contains(&breakage) {
+            println!(
+                "{}",
+                tr_args("pre-hook-abort", &[("package", package.as_str().into())])
+            );
+            abort = true;
+        }
+    }
+    abort
+}
+
+/// The reboot and session restart package lists with their patterns expanded against `db`.
+///
+/// The session restart list includes the dependency closure of `--session-dependency-packages`,
+/// except for packages which already require a reboot.
+fn package_lists(args: &Args, db: &alpm::Db) -> (Vec<PackageRule>, Vec<PackageRule>) {
+    let reboot_packages = package_list::expand_in_db(&args.reboot_packages, db);
+    let mut session_restart_packages =
+        package_list::expand_in_db(&args.session_restart_packages, db);
+    for rule in package_list::dependency_closure_in_db(&args.session_dependency_packages, db) {
+        let listed = |rules: &[PackageRule]| rules.iter().any(|known| known.name == rule.name);
+        if !listed(&reboot_packages) && !listed(&session_restart_packages) {
+            session_restart_packages.push(rule);
+        }
+    }
+    (reboot_packages, session_restart_packages)
+}
+
+/// Open the local pacman database.
+fn open_pacman_db() -> Result<alpm::Alpm> {
+    alpm::Alpm::new("/", "/var/lib/pacman/")
+        .context("Could not open pacman database at /var/lib/pacman")
+}
 
-    // Initialize Pacman database
-    let alpm = alpm::Alpm::new("/", "/var/lib/pacman/")
-        .expect("Could not open pacman database at /var/lib/pacman");
+/// The package of the running kernel, `None` if it can't be detected.
+fn running_kernel_package() -> Option<String> {
+    KernelInfo::from_uname()
+        .map(|kernel_info| kernel_info.package_name)
+        .inspect_err(|err| warn!("Could not detect running kernel: {err:#}"))
+        .ok()
+}
+
+/// Run all checks against the local pacman database, fails if none could be set up.
+///
+/// With `targets` only those packages are checked.
+fn run_checks(
+    args: &Args,
+    targets: Option<&[String]>,
+    pacman_log: &PacmanLog,
+) -> Result<CheckReport> {
+    let alpm = open_pacman_db()?;
     let db = alpm.localdb();
 
     let mut checkers: Vec<Box<dyn Check>> = vec![];
 
     match KernelChecker::new(db, args.verbose) {
-        Ok(kernel_checker) => checkers.push(Box::new(kernel_checker)),
+        Ok(kernel_checker) => {
+            if targets.is_none_or(|targets| {
+                targets
+                    .iter()
+                    .any(|target| target == kernel_checker.package_name())
+            }) {
+                checkers.push(Box::new(kernel_checker))
+            }
+        }
         Err(err) => {
             error!("Could not create kernel checker: {err:#}")
         }
     }
 
-    match CriticalPackagesCheck::new(
-        args.reboot_packages,
-        args.session_restart_packages,
-        db,
-        args.verbose,
-    ) {
+    let (mut reboot_packages, mut session_restart_packages) = package_lists(args, db);
+    if let Some(targets) = targets {
+        reboot_packages = hook::filter_targets(&reboot_packages, targets);
+        session_restart_packages = hook::filter_targets(&session_restart_packages, targets);
+    }
+    match CriticalPackagesCheck::new(reboot_packages, session_restart_packages, db, pacman_log) {
         Ok(critical_packages_checker) => checkers.push(Box::new(critical_packages_checker)),
         Err(err) => {
             error!("Could not create critical package checker: {err:#}")
         }
     }
 
-    let result = checkers
+    checkers
         .iter()
         .map(|v| v.check())
-        .max()
-        .expect("No checkers could run");
+        .reduce(CheckReport::merge)
+        .ok_or_else(|| anyhow!("No checkers could run"))
+}
 
-    if result > CheckResult::Nothing {
-        println!("{}", result.summary());
-        if !args.disable_notification {
-            Notification::new()
-                .summary(result.summary())
-                .body(result.body())
-                .timeout(args.notification_timeout)
-                .show()
-                .map_err(|e| error!("Couldn't send notification: {}", e))
-                .ok();
+/// Print the result, show a notification and remember what we reported.
+///
+/// A `partial` report only covers some packages, e.g. the targets of a pacman hook. It only
+/// notifies about its findings and leaves the remembered report and notification alone.
+fn report_result(args: &Args, config: &Config, report: &CheckReport, partial: bool) {
+    let result = report.result;
+    let mut state = State::load().unwrap_or_else(|err| {
+        warn!("Could not load state: {err:#}");
+        State::default()
+    });
+    if !partial {
+        state.update(report);
+    }
+    let now = OffsetDateTime::now_utc();
+
+    let message = Message::for_report(&config.messages, report);
+    let mut show_notification = false;
+    if let Some(Command::Ack) = args.command {
+        state.acknowledge();
+        println!(
+            "{}",
+            tr_args(
+                "acknowledged",
+                &[("summary", message.summary.as_str().into())]
+            )
+        );
+    } else if let Some(snooze) = args.snooze {
+        state.snooze_until((now + snooze).unix_timestamp());
+        println!(
+            "{}",
+            tr_args("snoozed", &[("summary", message.summary.as_str().into())])
+        );
+    } else if result > CheckResult::Nothing {
+        // Acknowledgement and snooze are for the remembered report, findings of a hook are new.
+        if !partial && state.is_suppressed(now.unix_timestamp()) {
+            if args.verbose {
+                println!(
+                    "{}",
+                    tr_args(
+                        "suppressed",
+                        &[("summary", message.summary.as_str().into())]
+                    )
+                );
+                print_details(report);
+            }
+        } else {
+            println!("{}", message.summary);
+            print_details(report);
+            show_notification = true;
         }
     }
+
+    if !args.disable_notification {
+        if show_notification {
+            let notifier = Notifier {
+                timeout: args.notification_timeout,
+                action_timeout: args.action_timeout(),
+                styles: config.notification.clone(),
+            };
+            let notified = notifier.notify(&result, &message, state.notification.as_ref());
+            state.notification = notified.shown;
+            if let Some(Action::RemindLater) = notified.action {
+                state.snooze_until((now + args.remind_after).unix_timestamp());
+            }
+        } else if !partial && let Some(notification) = state.notification.take() {
+            // Nothing to notify about (anymore), so don't leave a stale notification on screen.
+            notification.close().unwrap_or_else(|err| warn!("{err:#}"));
+        }
+    }
+
+    state
+        .save()
+        .unwrap_or_else(|err| warn!("Could not save state: {err:#}"));
 }
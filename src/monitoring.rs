@@ -0,0 +1,220 @@
+//! Output for monitoring systems like Nagios, Icinga and Checkmk.
+
+use crate::checks::{CheckReport, CheckResult};
+use clap::ValueEnum;
+
+/// Name of the service in the monitoring output.
+const SERVICE_NAME: &str = "REBOOT_ARCH_BTW";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Monitoring {
+    /// Nagios plugin output, also understood by Icinga. The exit code is the status.
+    Nagios,
+    /// Checkmk local check output. The status is part of the output.
+    Checkmk,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok = 0,
+    Warning = 1,
+    Critical = 2,
+    /// We couldn't check, because a pacman transaction is in progress or the checks failed.
+    Unknown = 3,
+}
+
+impl Status {
+    pub fn for_result(result: CheckResult) -> Status {
+        match result {
+            CheckResult::Nothing => Status::Ok,
+            CheckResult::RestartSession | CheckResult::Reboot => Status::Warning,
+            CheckResult::KernelUpdate => Status::Critical,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Warning => "WARNING",
+            Status::Critical => "CRITICAL",
+            Status::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Performance data about a report.
+#[derive(Debug, Default, PartialEq)]
+pub struct PerfData {
+    /// Days since the first upgrade after boot of a package requiring a reboot.
+    pub days_pending: f64,
+    /// Number of packages which got updated since the session started.
+    pub outdated_session_packages: usize,
+    /// Number of upgrades of the running kernel's package since boot, 0 if it is up to date.
+    pub kernel_upgrades: usize,
+}
+
+impl PerfData {
+    /// `pending_since` is the result of `CheckReport::reboot_pending_since` and
+    /// `kernel_upgrades` the number of kernel upgrades since boot, as far as the pacman log knows.
+    pub fn new(
+        report: &CheckReport,
+        now: i64,
+        pending_since: Option<i64>,
+        kernel_upgrades: usize,
+    ) -> PerfData {
+        let kernel_update = report.result == CheckResult::KernelUpdate;
+        PerfData {
+            days_pending: pending_since
+                .map_or(0.0, |since| (now - since).max(0) as f64 / (3600.0 * 24.0)),
+            outdated_session_packages: report
+                .packages
+                .iter()
+                .filter(|package| package.result == CheckResult::RestartSession)
+                .count(),
+            // The pacman log might have been rotated, but we know of at least one.
+            kernel_upgrades: if kernel_update {
+                kernel_upgrades.max(1)
+            } else {
+                0
+            },
+        }
+    }
+
+    fn values(&self) -> [String; 3] {
+        [
+            format!("days_pending={:.2}", self.days_pending),
+            format!(
+                "outdated_session_packages={}",
+                self.outdated_session_packages
+            ),
+            format!("kernel_upgrades={}", self.kernel_upgrades),
+        ]
+    }
+}
+
+impl Monitoring {
+    /// Format the status line, followed by the `details` lines.
+    pub fn format(
+        &self,
+        status: Status,
+        summary: &str,
+        details: &[String],
+        perf_data: Option<&PerfData>,
+    ) -> String {
+        let values = perf_data.map(PerfData::values);
+        match self {
+            Monitoring::Nagios => {
+                let mut output = format!("{SERVICE_NAME} {} - {summary}", status.label());
+                if let Some(values) = values {
+                    output += &format!(" | {}", values.join(" "));
+                }
+                for line in details {
+                    output += &format!("\n{line}");
+                }
+                output
+            }
+            Monitoring::Checkmk => {
+                let perf_data = values.map_or_else(|| "-".to_owned(), |values| values.join("|"));
+                let mut output = format!("{} {SERVICE_NAME} {perf_data} {summary}", status as u8);
+                // Checkmk shows an escaped newline as long output.
+                for line in details {
+                    output += &format!("\\n{line}");
+                }
+                output
+            }
+        }
+    }
+
+    /// The exit code to use after printing the output.
+    pub fn exit_code(&self, status: Status) -> i32 {
+        match self {
+            Monitoring::Nagios => status as i32,
+            Monitoring::Checkmk => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::{TriggeringPackage, triggering_package};
+
+    fn package(name: &str, install_date: i64, result: CheckResult) -> TriggeringPackage {
+        let mut package = triggering_package(name, result);
+        package.info.install_date = Some(install_date);
+        package
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(Status::for_result(CheckResult::Nothing), Status::Ok);
+        assert_eq!(
+            Status::for_result(CheckResult::RestartSession),
+            Status::Warning
+        );
+        assert_eq!(Status::for_result(CheckResult::Reboot), Status::Warning);
+        assert_eq!(
+            Status::for_result(CheckResult::KernelUpdate),
+            Status::Critical
+        );
+    }
+
+    #[test]
+    fn test_perf_data() {
+        let mut report = CheckReport::default();
+        report.add_package(package("linux", 1000, CheckResult::KernelUpdate));
+        report.add_package(package("systemd", 100, CheckResult::Reboot));
+        report.add_package(package("xorg-server", 50, CheckResult::RestartSession));
+        let perf_data = PerfData::new(&report, 100 + 3600 * 36, Some(100), 0);
+        assert_eq!(
+            perf_data,
+            PerfData {
+                days_pending: 1.5,
+                outdated_session_packages: 1,
+                kernel_upgrades: 1,
+            }
+        );
+        assert_eq!(
+            PerfData::new(&CheckReport::default(), 100, None, 2),
+            PerfData::default()
+        );
+    }
+
+    #[test]
+    fn test_format_nagios() {
+        let perf_data = PerfData {
+            days_pending: 1.5,
+            outdated_session_packages: 0,
+            kernel_upgrades: 2,
+        };
+        assert_eq!(
+            Monitoring::Nagios.format(
+                Status::Critical,
+                "Reboot arch btw",
+                &["Kernel changed".to_owned()],
+                Some(&perf_data)
+            ),
+            "REBOOT_ARCH_BTW CRITICAL - Reboot arch btw | days_pending=1.50 \
+             outdated_session_packages=0 kernel_upgrades=2\nKernel changed"
+        );
+        assert_eq!(
+            Monitoring::Nagios.format(Status::Unknown, "Busy", &[], None),
+            "REBOOT_ARCH_BTW UNKNOWN - Busy"
+        );
+    }
+
+    #[test]
+    fn test_format_checkmk() {
+        assert_eq!(
+            Monitoring::Checkmk.format(Status::Ok, "All good", &[], Some(&PerfData::default())),
+            "0 REBOOT_ARCH_BTW days_pending=0.00|outdated_session_packages=0|kernel_upgrades=0 \
+             All good"
+        );
+        assert_eq!(
+            Monitoring::Checkmk.format(Status::Unknown, "Busy", &["a".to_owned()], None),
+            "3 REBOOT_ARCH_BTW - Busy\\na"
+        );
+        assert_eq!(Monitoring::Checkmk.exit_code(Status::Critical), 0);
+        assert_eq!(Monitoring::Nagios.exit_code(Status::Critical), 2);
+    }
+}
@@ -0,0 +1,59 @@
+//! A message of the day snippet, shown by `pam_motd` on login, e.g. via SSH.
+
+use crate::checks::{CheckReport, CheckResult};
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+pub const MOTD_PATH: &str = "/run/motd.d/50-reboot-arch-btw";
+
+/// Write the summary and details of `report` to `path`, or remove it if there is nothing to tell.
+pub fn update(path: &Path, report: &CheckReport) -> Result<()> {
+    if report.result == CheckResult::Nothing {
+        return match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                Err(err).with_context(|| anyhow!("Could not remove {}", path.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| anyhow!("Could not create {}", dir.display()))?;
+    }
+    fs::write(path, snippet(report)).with_context(|| anyhow!("Could not write {}", path.display()))
+}
+
+fn snippet(report: &CheckReport) -> String {
+    let mut snippet = format!("{}\n", report.result.summary());
+    for line in report.details() {
+        snippet += &format!(" {line}\n");
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::triggering_package;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn test_update() {
+        let dir = TestDir::new("motd");
+        let path = dir.path().join("motd.d/50-reboot-arch-btw");
+        let mut systemd = triggering_package("systemd", CheckResult::Reboot);
+        systemd.info.version = "256.5-1".to_owned();
+        systemd.previous_version = Some("256.4-1".to_owned());
+        let mut report = CheckReport::default();
+        report.add_package(systemd);
+
+        update(&path, &report).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Reboot arch btw\n systemd 256.4-1 → 256.5-1 (unknown)\n"
+        );
+        update(&path, &CheckReport::default()).unwrap();
+        assert!(!path.exists());
+    }
+}
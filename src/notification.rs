@@ -0,0 +1,334 @@
+use crate::checks::CheckResult;
+use crate::config::NotificationStyles;
+use crate::i18n::tr;
+use crate::logind;
+use crate::template::Message;
+use anyhow::{Context, Result};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use log::{error, info, warn};
+use notify_rust::{Hint, Notification, Timeout};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const NOTIFICATIONS_BUS_NAME: &str = "org.freedesktop.Notifications";
+const DBUS_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// Buttons offered on the desktop notification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    RebootNow,
+    LogOut,
+    RemindLater,
+}
+
+impl Action {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Action::RebootNow => "reboot",
+            Action::LogOut => "logout",
+            Action::RemindLater => "remind-later",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Action::RebootNow => tr("action-reboot-now"),
+            Action::LogOut => tr("action-log-out"),
+            Action::RemindLater => tr("action-remind-later"),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Action> {
+        [Action::RebootNow, Action::LogOut, Action::RemindLater]
+            .into_iter()
+            .find(|action| action.id() == id)
+    }
+
+    /// The actions which make sense for the given result.
+    pub fn for_result(result: &CheckResult) -> &'static [Action] {
+        match result {
+            CheckResult::Nothing => &[],
+            CheckResult::RestartSession => &[Action::LogOut, Action::RemindLater],
+            CheckResult::Reboot | CheckResult::KernelUpdate => {
+                &[Action::RebootNow, Action::RemindLater]
+            }
+        }
+    }
+}
+
+pub struct Notifier {
+    /// Timeout of the notification itself, as passed to the notification server.
+    pub timeout: Timeout,
+    /// How long to wait for a click on one of the buttons. Zero disables the buttons.
+    pub action_timeout: Duration,
+    pub styles: NotificationStyles,
+}
+
+/// A notification shown by an earlier run, so later runs can replace or close it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShownNotification {
+    pub id: u32,
+    /// Unique bus name of the notification server which showed it.
+    ///
+    /// Notification IDs are only meaningful to the server instance which handed them out. After a
+    /// reboot or a restart of the server the same ID may belong to a completely different
+    /// notification.
+    pub server: String,
+}
+
+impl ShownNotification {
+    fn new(id: u32) -> Result<ShownNotification> {
+        Ok(ShownNotification {
+            id,
+            server: notification_server()?,
+        })
+    }
+
+    /// The ID of the notification if the server which showed it is still running.
+    fn current_id(&self) -> Option<u32> {
+        match notification_server() {
+            Ok(server) if server == self.server => Some(self.id),
+            _ => None,
+        }
+    }
+
+    /// Close the notification if it is still around.
+    pub fn close(&self) -> Result<()> {
+        let Some(id) = self.current_id() else {
+            info!(
+                "Notification server changed, not closing notification {}",
+                self.id
+            );
+            return Ok(());
+        };
+        let connection = Connection::new_session()?;
+        let proxy = connection.with_proxy(
+            NOTIFICATIONS_BUS_NAME,
+            "/org/freedesktop/Notifications",
+            DBUS_TIMEOUT,
+        );
+        proxy
+            .method_call::<(), _, _, _>(NOTIFICATIONS_BUS_NAME, "CloseNotification", (id,))
+            .context("Could not close notification")
+    }
+}
+
+/// Unique bus name of the current owner of the notification service.
+fn notification_server() -> Result<String> {
+    let connection = Connection::new_session()?;
+    let proxy = connection.with_proxy(
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        DBUS_TIMEOUT,
+    );
+    let (owner,): (String,) = proxy
+        .method_call(
+            "org.freedesktop.DBus",
+            "GetNameOwner",
+            (NOTIFICATIONS_BUS_NAME,),
+        )
+        .context("Could not find notification server")?;
+    Ok(owner)
+}
+
+/// What happened to a notification we showed.
+#[derive(Debug, Default)]
+pub struct Notified {
+    pub shown: Option<ShownNotification>,
+    pub action: Option<Action>,
+}
+
+impl Notifier {
+    /// Show `message` as notification for `result` and run the action the user picked.
+    ///
+    /// `previous` gets replaced by the new notification instead of stacking another one on top.
+    /// The picked action is returned, so the caller can take care of "Remind me later".
+    pub fn notify(
+        &self,
+        result: &CheckResult,
+        message: &Message,
+        previous: Option<&ShownNotification>,
+    ) -> Notified {
+        let notified = self.show(
+            result,
+            message,
+            previous.and_then(ShownNotification::current_id),
+        );
+        match notified.action {
+            Some(Action::RebootNow) => {
+                logind::reboot().unwrap_or_else(|err| error!("{err:#}"));
+            }
+            Some(Action::LogOut) => {
+                logind::terminate_session().unwrap_or_else(|err| error!("{err:#}"));
+            }
+            Some(Action::RemindLater) | None => {}
+        }
+        notified
+    }
+
+    /// Show the notification and wait for the user to click one of its buttons.
+    ///
+    /// The action is `None` if the notification was closed, the wait timed out or actions are
+    /// disabled.
+    fn show(&self, result: &CheckResult, message: &Message, replaces_id: Option<u32>) -> Notified {
+        let mut notification = Notification::new();
+        notification
+            .summary(&message.summary)
+            .body(&message.body)
+            .timeout(self.timeout);
+        self.styles.for_result(result).apply(&mut notification);
+        if let Some(id) = replaces_id {
+            notification.id(id);
+        }
+
+        if self.action_timeout.is_zero() {
+            return match notification.show() {
+                Ok(handle) => Notified {
+                    shown: Self::remember(handle.id()),
+                    action: None,
+                },
+                Err(e) => {
+                    error!("Couldn't send notification: {}", e);
+                    Notified::default()
+                }
+            };
+        }
+
+        for action in Action::for_result(result) {
+            notification.action(action.id(), &action.label());
+        }
+
+        match self.show_and_wait(&notification, replaces_id) {
+            Ok(notified) => notified,
+            Err(err) => {
+                error!("Couldn't send notification: {err:#}");
+                Notified::default()
+            }
+        }
+    }
+
+    /// Show the notification and wait for a click until the action timeout.
+    ///
+    /// notify-rust can only wait until the notification gets closed, which may be never. So we
+    /// show it and listen for the signals on our own connection, which stops listening after the
+    /// timeout. Servers like GNOME Shell only send the signals to the connection which showed the
+    /// notification.
+    fn show_and_wait(
+        &self,
+        notification: &Notification,
+        replaces_id: Option<u32>,
+    ) -> Result<Notified> {
+        let connection = Connection::new_session()?;
+        let (sender, receiver) = mpsc::channel();
+        let closed_sender = sender.clone();
+        // Listen before showing, so a quick click isn't missed.
+        connection.add_match(
+            MatchRule::new_signal(NOTIFICATIONS_BUS_NAME, "ActionInvoked"),
+            move |(id, action): (u32, String), _: &Connection, _: &dbus::Message| {
+                sender.send((id, Action::from_id(&action))).ok();
+                true
+            },
+        )?;
+        connection.add_match(
+            MatchRule::new_signal(NOTIFICATIONS_BUS_NAME, "NotificationClosed"),
+            move |(id, _reason): (u32, u32), _: &Connection, _: &dbus::Message| {
+                closed_sender.send((id, None)).ok();
+                true
+            },
+        )?;
+        let proxy = connection.with_proxy(
+            NOTIFICATIONS_BUS_NAME,
+            "/org/freedesktop/Notifications",
+            DBUS_TIMEOUT,
+        );
+        let (id,): (u32,) = proxy.method_call(
+            NOTIFICATIONS_BUS_NAME,
+            "Notify",
+            (
+                &notification.appname,
+                replaces_id.unwrap_or(0),
+                &notification.icon,
+                &notification.summary,
+                &notification.body,
+                &notification.actions,
+                hints(notification),
+                i32::from(notification.timeout),
+            ),
+        )?;
+        let mut notified = Notified {
+            shown: Self::remember(id),
+            action: None,
+        };
+        let deadline = Instant::now() + self.action_timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            if let Err(err) = connection.process(remaining) {
+                error!("Couldn't wait for notification action: {err}");
+                break;
+            }
+            if let Some((_, action)) = receiver.try_iter().find(|(closed, _)| *closed == id) {
+                notified.action = action;
+                break;
+            }
+        }
+        Ok(notified)
+    }
+
+    fn remember(id: u32) -> Option<ShownNotification> {
+        ShownNotification::new(id)
+            .map_err(|err| warn!("Could not remember notification: {err:#}"))
+            .ok()
+    }
+}
+
+/// The hints of `notification` as D-Bus dictionary.
+///
+/// Only covers the hints `NotificationStyle::apply` sets.
+fn hints(notification: &Notification) -> PropMap {
+    notification
+        .hints
+        .iter()
+        .filter_map(|hint| {
+            let (name, value): (&str, Box<dyn RefArg>) = match hint {
+                Hint::Urgency(urgency) => ("urgency", Box::new(*urgency as u8)),
+                Hint::Category(category) => ("category", Box::new(category.clone())),
+                Hint::Resident(resident) => ("resident", Box::new(*resident)),
+                Hint::Transient(transient) => ("transient", Box::new(*transient)),
+                _ => return None,
+            };
+            Some((name.to_owned(), Variant(value)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_action_id_roundtrip() {
+        for action in [Action::RebootNow, Action::LogOut, Action::RemindLater] {
+            assert_eq!(Action::from_id(action.id()), Some(action));
+        }
+        assert_eq!(Action::from_id("default"), None);
+    }
+
+    #[test]
+    fn test_actions_match_severity() {
+        assert!(Action::for_result(&CheckResult::Nothing).is_empty());
+        assert_eq!(
+            Action::for_result(&CheckResult::RestartSession),
+            &[Action::LogOut, Action::RemindLater]
+        );
+        assert_eq!(
+            Action::for_result(&CheckResult::KernelUpdate),
+            &[Action::RebootNow, Action::RemindLater]
+        );
+    }
+}
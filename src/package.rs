@@ -1,6 +1,10 @@
+use crate::duration::format_duration_ago;
+use crate::i18n::tr;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct PackageInfo {
     pub version: String,
     pub install_date: Option<i64>,
@@ -57,7 +61,7 @@ impl PackageInfo {
     pub fn installed_reltime(&self) -> String {
         let install_date = match self.install_date {
             Some(d) => d,
-            None => return "unknown".to_string(),
+            None => return tr("unknown"),
         } as u64;
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -66,15 +70,7 @@ impl PackageInfo {
         // Clamp to 0 if the install date is in the future (e.g. clock skew) to
         // avoid wrapping into a garbage value (overflow checks are off in release).
         let delta = now.saturating_sub(install_date);
-        if delta < 60 {
-            format!("{} seconds ago", delta)
-        } else if delta < 7200 {
-            format!("{} minutes ago", delta / 60)
-        } else if delta < 3600 * 36 {
-            format!("{} hours ago", delta / 3600)
-        } else {
-            format!("{} days ago", delta / (3600 * 24))
-        }
+        format_duration_ago(delta)
     }
 }
 
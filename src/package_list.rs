@@ -0,0 +1,357 @@
+//! Patterns in the package lists, expanded against the local database at check time.
+//!
+//! Besides plain names the lists accept globs like `nvidia*`, groups like `@plasma` and regular
+//! expressions like `/linux-firmware-.*/`. A plain name which isn't installed matches the
+//! packages providing it, so renamed and split packages keep working.
+//!
+//! Session roots like a compositor expand to their runtime dependency closure instead.
+//!
+//! Every pattern can be followed by the change level which triggers it, like `systemd:minor` to
+//! ignore rebuilds and patch releases. By default any change does.
+
+use crate::version::ChangeLevel;
+use anyhow::{Context, Result, anyhow};
+use log::{error, warn};
+use regex_lite::Regex;
+use std::collections::VecDeque;
+
+#[derive(Debug)]
+pub enum PackagePattern {
+    /// A package name, or the name of something packages provide.
+    Name(String),
+    /// A name with `*` and `?` wildcards.
+    Glob(String),
+    /// A pacman group, written as `@group`.
+    Group(String),
+    /// A regular expression matching the whole name, written as `/regex/`.
+    Regex(Regex),
+}
+
+/// An installed package from a list, with the change which triggers it.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PackageRule {
+    pub name: String,
+    pub min_change: ChangeLevel,
+}
+
+/// What patterns can match of an installed package.
+#[derive(Debug)]
+pub struct PackageEntry<'a> {
+    pub name: &'a str,
+    pub groups: Vec<&'a str>,
+    pub provides: Vec<&'a str>,
+}
+
+impl PackageEntry<'_> {
+    pub fn from_package(package: &alpm::Package) -> PackageEntry<'_> {
+        PackageEntry {
+            name: package.name(),
+            groups: package.groups().iter().collect(),
+            provides: package.provides().iter().map(|dep| dep.name()).collect(),
+        }
+    }
+}
+
+impl PackagePattern {
+    pub fn parse(pattern: &str) -> Result<PackagePattern> {
+        if let Some(group) = pattern.strip_prefix('@') {
+            Ok(PackagePattern::Group(group.to_owned()))
+        } else if let Some(regex) = pattern
+            .strip_prefix('/')
+            .and_then(|regex| regex.strip_suffix('/'))
+        {
+            let regex = Regex::new(&format!("^(?:{regex})$"))
+                .with_context(|| anyhow!("Invalid regular expression in {pattern}"))?;
+            Ok(PackagePattern::Regex(regex))
+        } else if pattern.contains(['*', '?']) {
+            Ok(PackagePattern::Glob(pattern.to_owned()))
+        } else {
+            Ok(PackagePattern::Name(pattern.to_owned()))
+        }
+    }
+
+    /// The names of the installed `packages` matching this pattern.
+    fn expand<'a>(&self, packages: &[PackageEntry<'a>]) -> Vec<&'a str> {
+        let matching = |predicate: &dyn Fn(&PackageEntry) -> bool| {
+            packages
+                .iter()
+                .filter(|package| predicate(package))
+                .map(|package| package.name)
+                .collect()
+        };
+        match self {
+            PackagePattern::Name(name) => {
+                if let Some(package) = packages.iter().find(|package| package.name == name) {
+                    vec![package.name]
+                } else {
+                    matching(&|package| package.provides.contains(&name.as_str()))
+                }
+            }
+            PackagePattern::Glob(glob) => matching(&|package| glob_matches(glob, package.name)),
+            PackagePattern::Group(group) => {
+                matching(&|package| package.groups.contains(&group.as_str()))
+            }
+            PackagePattern::Regex(regex) => matching(&|package| regex.is_match(package.name)),
+        }
+    }
+}
+
+/// Whether `name` matches `glob`, where `*` matches any number of characters and `?` one.
+fn glob_matches(glob: &str, name: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut g, mut n) = (0, 0);
+    // Where to continue after the last `*` if the rest doesn't match.
+    let mut backtrack = None;
+    while n < name.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, n));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                g += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    g = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+/// Split off the change level, e.g. "minor" from `systemd:minor`.
+fn split_change_level(pattern: &str) -> Result<(&str, ChangeLevel)> {
+    match pattern.rsplit_once(':') {
+        // A colon in a regular expression is no change level.
+        Some((pattern, level)) if !level.contains('/') => Ok((pattern, level.parse()?)),
+        _ => Ok((pattern, ChangeLevel::default())),
+    }
+}
+
+/// Expand `patterns` to rules for the installed `packages`, without duplicates.
+pub fn expand(patterns: &[String], packages: &[PackageEntry]) -> Vec<PackageRule> {
+    let mut rules: Vec<PackageRule> = vec![];
+    for pattern in patterns {
+        let parsed = split_change_level(pattern).and_then(|(package_pattern, level)| {
+            Ok((PackagePattern::parse(package_pattern)?, level))
+        });
+        let (package_pattern, min_change) = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                error!("{err:#}");
+                continue;
+            }
+        };
+        let names = package_pattern.expand(packages);
+        if names.is_empty() {
+            warn!("No installed package matches {pattern}");
+        }
+        for name in names {
+            if !rules.iter().any(|rule| rule.name == name) {
+                rules.push(PackageRule {
+                    name: name.to_owned(),
+                    min_change,
+                });
+            }
+        }
+    }
+    rules
+}
+
+/// Expand `patterns` against the packages in `db`.
+pub fn expand_in_db(patterns: &[String], db: &alpm::Db) -> Vec<PackageRule> {
+    let packages: Vec<PackageEntry> = db.pkgs().iter().map(PackageEntry::from_package).collect();
+    expand(patterns, &packages)
+}
+
+/// `roots` and all packages they depend on at runtime, directly or indirectly.
+///
+/// `depends` returns the names of the installed packages satisfying the dependencies of a package.
+pub fn dependency_closure(roots: &[String], depends: impl Fn(&str) -> Vec<String>) -> Vec<String> {
+    let mut closure: Vec<String> = vec![];
+    let mut queue: VecDeque<String> = roots.iter().cloned().collect();
+    while let Some(name) = queue.pop_front() {
+        if closure.contains(&name) {
+            continue;
+        }
+        queue.extend(depends(&name));
+        closure.push(name);
+    }
+    closure
+}
+
+/// Expand the `roots` patterns and their runtime dependency closure against the packages in `db`.
+///
+/// The change level of a root applies to its whole closure.
+pub fn dependency_closure_in_db(roots: &[String], db: &alpm::Db) -> Vec<PackageRule> {
+    let installed = db.pkgs();
+    let depends = |name: &str| {
+        let Ok(package) = db.pkg(name) else {
+            return vec![];
+        };
+        package
+            .depends()
+            .iter()
+            .filter_map(|dep| installed.find_satisfier(dep.to_string()))
+            .map(|package| package.name().to_owned())
+            .collect()
+    };
+    let mut rules: Vec<PackageRule> = vec![];
+    for root in expand_in_db(roots, db) {
+        for name in dependency_closure(std::slice::from_ref(&root.name), depends) {
+            if !rules.iter().any(|rule| rule.name == name) {
+                rules.push(PackageRule {
+                    name,
+                    min_change: root.min_change,
+                });
+            }
+        }
+    }
+    rules
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry<'a>(name: &'a str, groups: &[&'a str], provides: &[&'a str]) -> PackageEntry<'a> {
+        PackageEntry {
+            name,
+            groups: groups.to_vec(),
+            provides: provides.to_vec(),
+        }
+    }
+
+    fn packages() -> Vec<PackageEntry<'static>> {
+        vec![
+            entry("bash", &[], &["sh"]),
+            entry("linux-firmware-intel", &[], &["linux-firmware"]),
+            entry("linux-firmware-amdgpu", &[], &["linux-firmware"]),
+            entry("nvidia-open", &[], &["nvidia"]),
+            entry("nvidia-utils", &[], &[]),
+            entry("plasma-workspace", &["plasma"], &[]),
+            entry("kwin", &["plasma"], &[]),
+            entry("systemd", &[], &[]),
+        ]
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn names(rules: Vec<PackageRule>) -> Vec<String> {
+        rules.into_iter().map(|rule| rule.name).collect()
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("nvidia*", "nvidia"));
+        assert!(glob_matches("nvidia*", "nvidia-utils"));
+        assert!(glob_matches("*-firmware-*", "linux-firmware-intel"));
+        assert!(glob_matches("linux-???", "linux-lts"));
+        assert!(!glob_matches("linux-???", "linux-zen6"));
+        assert!(!glob_matches("nvidia*", "lib32-nvidia-utils"));
+        assert!(glob_matches("*a*b", "aXbYb"));
+    }
+
+    #[test]
+    fn test_expand_names() {
+        assert_eq!(
+            names(expand(&strings(&["systemd", "amd-ucode"]), &packages())),
+            strings(&["systemd"])
+        );
+    }
+
+    #[test]
+    fn test_expand_provides() {
+        // linux-firmware got split, so the name matches the packages providing it.
+        assert_eq!(
+            names(expand(&strings(&["linux-firmware", "sh"]), &packages())),
+            strings(&["linux-firmware-intel", "linux-firmware-amdgpu", "bash"])
+        );
+    }
+
+    #[test]
+    fn test_expand_patterns() {
+        assert_eq!(
+            names(expand(&strings(&["nvidia*", "@plasma"]), &packages())),
+            strings(&["nvidia-open", "nvidia-utils", "plasma-workspace", "kwin"])
+        );
+        assert_eq!(
+            names(expand(
+                &strings(&["/linux-firmware-(intel|amdgpu)/"]),
+                &packages()
+            )),
+            strings(&["linux-firmware-intel", "linux-firmware-amdgpu"])
+        );
+        // The regex has to match the whole name.
+        assert!(expand(&strings(&["/firmware/"]), &packages()).is_empty());
+    }
+
+    #[test]
+    fn test_expand_without_duplicates() {
+        assert_eq!(
+            names(expand(&strings(&["nvidia*", "nvidia-utils"]), &packages())),
+            strings(&["nvidia-open", "nvidia-utils"])
+        );
+    }
+
+    #[test]
+    fn test_expand_change_level() {
+        assert_eq!(
+            expand(&strings(&["systemd:minor", "nvidia*"]), &packages()),
+            vec![
+                PackageRule {
+                    name: "systemd".to_owned(),
+                    min_change: ChangeLevel::Minor,
+                },
+                PackageRule {
+                    name: "nvidia-open".to_owned(),
+                    min_change: ChangeLevel::Pkgrel,
+                },
+                PackageRule {
+                    name: "nvidia-utils".to_owned(),
+                    min_change: ChangeLevel::Pkgrel,
+                },
+            ]
+        );
+        assert_eq!(
+            names(expand(
+                &strings(&["/nvidia-(open|dkms)/:pkgver"]),
+                &packages()
+            )),
+            strings(&["nvidia-open"])
+        );
+        assert!(expand(&strings(&["systemd:patch"]), &packages()).is_empty());
+    }
+
+    #[test]
+    fn test_dependency_closure() {
+        let depends = |name: &str| match name {
+            "sway" => strings(&["wlroots", "glibc"]),
+            "wlroots" => strings(&["mesa", "glibc"]),
+            "mesa" => strings(&["glibc"]),
+            // Dependency cycles must not loop forever.
+            "glibc" => strings(&["sway"]),
+            _ => vec![],
+        };
+        assert_eq!(
+            dependency_closure(&strings(&["sway"]), depends),
+            strings(&["sway", "wlroots", "glibc", "mesa"])
+        );
+        assert!(dependency_closure(&[], depends).is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_regex() {
+        assert!(PackagePattern::parse("/nvidia(/").is_err());
+        assert!(expand(&strings(&["/nvidia(/"]), &packages()).is_empty());
+    }
+}
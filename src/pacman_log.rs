@@ -0,0 +1,202 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use time::OffsetDateTime;
+use time::format_description::BorrowedFormatItem;
+
+const PACMAN_LOG_PATH: &str = "/var/log/pacman.log";
+const TIME_FORMAT: &str =
+    "[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory][offset_minute]";
+
+/// A package upgrade recorded in the pacman log.
+///
+/// Downgrades and reinstalls replace the installed files as well, so they count as upgrades. A
+/// reinstall has the same old and new version.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Upgrade {
+    pub time: i64,
+    pub package: String,
+    pub old_version: String,
+    pub new_version: String,
+}
+
+/// The upgrades found in `/var/log/pacman.log`, oldest first.
+///
+/// The local database only knows the currently installed version of a package, so this is where
+/// we find out which version got replaced.
+#[derive(Debug, Default)]
+pub struct PacmanLog {
+    upgrades: Vec<Upgrade>,
+}
+
+impl PacmanLog {
+    pub fn read() -> Result<PacmanLog> {
+        // Scriptlet output gets logged as is and may not be valid UTF-8.
+        let content = fs::read(PACMAN_LOG_PATH)
+            .with_context(|| anyhow!("Could not read {PACMAN_LOG_PATH}"))?;
+        Ok(Self::parse(&String::from_utf8_lossy(&content)))
+    }
+
+    pub fn parse(content: &str) -> PacmanLog {
+        let format = time::format_description::parse_borrowed::<2>(TIME_FORMAT)
+            .expect("valid format description");
+        let upgrades = content
+            .lines()
+            .filter_map(|line| Self::parse_upgrade(line, &format))
+            .collect();
+        PacmanLog { upgrades }
+    }
+
+    /// Parse a line like
+    /// `[2024-08-20T10:12:33+0200] [ALPM] upgraded systemd (256.4-1 -> 256.5-1)`, the same with
+    /// `downgraded` or `[2024-08-20T10:12:33+0200] [ALPM] reinstalled systemd (256.5-1)`.
+    fn parse_upgrade(line: &str, format: &[BorrowedFormatItem]) -> Option<Upgrade> {
+        let (time, rest) = line.strip_prefix('[')?.split_once("] [ALPM] ")?;
+        let (operation, rest) = rest.split_once(' ')?;
+        let (package, versions) = rest.split_once(" (")?;
+        let versions = versions.strip_suffix(')')?;
+        let (old_version, new_version) = match operation {
+            "upgraded" | "downgraded" => versions.split_once(" -> ")?,
+            "reinstalled" => (versions, versions),
+            _ => return None,
+        };
+        // Old log entries use a different time format, those are too old to be of interest anyway.
+        let time = OffsetDateTime::parse(time, format).ok()?;
+        Some(Upgrade {
+            time: time.unix_timestamp(),
+            package: package.to_owned(),
+            old_version: old_version.to_owned(),
+            new_version: new_version.to_owned(),
+        })
+    }
+
+    /// The version `package` had at unix timestamp `time`, if it got upgraded since then.
+    pub fn version_at(&self, package: &str, time: i64) -> Option<&str> {
+        self.upgrades
+            .iter()
+            .find(|upgrade| upgrade.time >= time && upgrade.package == package)
+            .map(|upgrade| upgrade.old_version.as_str())
+    }
+
+    /// Unix timestamp of the first upgrade of `package` since unix timestamp `time`.
+    pub fn first_upgrade_since(&self, package: &str, time: i64) -> Option<i64> {
+        self.upgrades
+            .iter()
+            .find(|upgrade| upgrade.time >= time && upgrade.package == package)
+            .map(|upgrade| upgrade.time)
+    }
+
+    /// How often the version of `package` changed since unix timestamp `time`, reinstalls don't
+    /// count.
+    pub fn upgrades_since(&self, package: &str, time: i64) -> usize {
+        self.upgrades
+            .iter()
+            .filter(|upgrade| upgrade.time >= time && upgrade.package == package)
+            .filter(|upgrade| upgrade.old_version != upgrade.new_version)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LOG: &str = "\
+[2024-08-19T09:00:00+0200] [PACMAN] starting full system upgrade
+[2024-08-19T09:00:05+0200] [ALPM] upgraded systemd (256.3-1 -> 256.4-1)
+[2024-08-20T10:12:33+0200] [ALPM] upgraded systemd (256.4-1 -> 256.5-1)
+[2024-08-20T10:12:34+0200] [ALPM] upgraded linux (6.10.5.arch1-1 -> 6.10.6.arch1-1)
+[2024-08-20T10:12:35+0200] [ALPM] installed foo (1.0-1)
+[2024-08-21T08:00:00+0200] [ALPM] upgraded systemd (256.5-1 -> 256.5-2)
+[2024-08-21T09:00:00+0200] [ALPM] downgraded mesa (1:24.2.1-1 -> 1:24.2.0-1)
+[2024-08-21T09:00:01+0200] [ALPM] reinstalled linux (6.10.6.arch1-1)
+";
+
+    fn timestamp(text: &str) -> i64 {
+        let format = time::format_description::parse_borrowed::<2>(TIME_FORMAT).unwrap();
+        OffsetDateTime::parse(text, &format)
+            .unwrap()
+            .unix_timestamp()
+    }
+
+    #[test]
+    fn test_parse_upgrades_only() {
+        let log = PacmanLog::parse(LOG);
+        assert_eq!(log.upgrades.len(), 6);
+        assert_eq!(
+            log.upgrades[1],
+            Upgrade {
+                time: timestamp("2024-08-20T10:12:33+0200"),
+                package: "systemd".to_owned(),
+                old_version: "256.4-1".to_owned(),
+                new_version: "256.5-1".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_downgrade_and_reinstall() {
+        let log = PacmanLog::parse(LOG);
+        assert_eq!(
+            log.upgrades[4],
+            Upgrade {
+                time: timestamp("2024-08-21T09:00:00+0200"),
+                package: "mesa".to_owned(),
+                old_version: "1:24.2.1-1".to_owned(),
+                new_version: "1:24.2.0-1".to_owned(),
+            }
+        );
+        assert_eq!(
+            log.upgrades[5],
+            Upgrade {
+                time: timestamp("2024-08-21T09:00:01+0200"),
+                package: "linux".to_owned(),
+                old_version: "6.10.6.arch1-1".to_owned(),
+                new_version: "6.10.6.arch1-1".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_version_at() {
+        let log = PacmanLog::parse(LOG);
+        let boot = timestamp("2024-08-20T08:00:00+0200");
+        // The first upgrade after boot tells us which version was running.
+        assert_eq!(log.version_at("systemd", boot), Some("256.4-1"));
+        assert_eq!(log.version_at("linux", boot), Some("6.10.5.arch1-1"));
+        assert_eq!(log.version_at("foo", boot), None);
+        assert_eq!(
+            log.version_at("systemd", timestamp("2024-08-22T00:00:00+0200")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_first_upgrade_since() {
+        let log = PacmanLog::parse(LOG);
+        let boot = timestamp("2024-08-20T08:00:00+0200");
+        assert_eq!(
+            log.first_upgrade_since("systemd", boot),
+            Some(timestamp("2024-08-20T10:12:33+0200"))
+        );
+        assert_eq!(log.first_upgrade_since("foo", boot), None);
+    }
+
+    #[test]
+    fn test_upgrades_since() {
+        let log = PacmanLog::parse(LOG);
+        let boot = timestamp("2024-08-20T08:00:00+0200");
+        assert_eq!(log.upgrades_since("systemd", boot), 2);
+        // The reinstall doesn't count.
+        assert_eq!(log.upgrades_since("linux", boot), 1);
+        assert_eq!(log.upgrades_since("foo", boot), 0);
+    }
+
+    #[test]
+    fn test_ignore_malformed_lines() {
+        let log = PacmanLog::parse(
+            "[2019-01-01 10:00] [ALPM] upgraded systemd (240-1 -> 240-2)\n\
+             [2024-08-20T10:12:33+0200] [ALPM] upgraded systemd 256.4-1 -> 256.5-1\n",
+        );
+        assert!(log.upgrades.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+//! Session restart packages of common desktop environments and compositors.
+
+use clap::ValueEnum;
+use log::{info, warn};
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SessionPreset {
+    Gnome,
+    Plasma,
+    Sway,
+    Hyprland,
+    Xfce,
+    I3,
+    /// Detect the desktop from XDG_CURRENT_DESKTOP or XDG_SESSION_DESKTOP.
+    Auto,
+}
+
+impl SessionPreset {
+    /// The preset to use, with `Auto` replaced by the detected desktop.
+    pub fn resolve(self) -> Option<SessionPreset> {
+        if self != SessionPreset::Auto {
+            return Some(self);
+        }
+        let current_desktop = env::var("XDG_CURRENT_DESKTOP").ok();
+        let session_desktop = env::var("XDG_SESSION_DESKTOP").ok();
+        let preset = Self::detect(current_desktop.as_deref(), session_desktop.as_deref());
+        match preset {
+            Some(preset) => info!("Detected session preset {preset:?}"),
+            None => warn!("Could not detect a session preset from {current_desktop:?}"),
+        }
+        preset
+    }
+
+    /// `XDG_CURRENT_DESKTOP` is a colon separated list like "ubuntu:GNOME", so every entry counts.
+    fn detect(
+        current_desktop: Option<&str>,
+        session_desktop: Option<&str>,
+    ) -> Option<SessionPreset> {
+        current_desktop
+            .into_iter()
+            .flat_map(|desktops| desktops.split(':'))
+            .chain(session_desktop)
+            .find_map(Self::from_desktop_name)
+    }
+
+    fn from_desktop_name(name: &str) -> Option<SessionPreset> {
+        match name.to_lowercase().as_str() {
+            "gnome" => Some(SessionPreset::Gnome),
+            "kde" | "plasma" | "plasmawayland" => Some(SessionPreset::Plasma),
+            "sway" => Some(SessionPreset::Sway),
+            "hyprland" => Some(SessionPreset::Hyprland),
+            "xfce" => Some(SessionPreset::Xfce),
+            "i3" => Some(SessionPreset::I3),
+            _ => None,
+        }
+    }
+
+    /// Patterns of the packages the running session keeps using.
+    pub fn packages(&self) -> &'static [&'static str] {
+        match self {
+            SessionPreset::Gnome => &[
+                "gnome-shell",
+                "mutter",
+                "gnome-session",
+                "gnome-settings-daemon",
+                "gdm",
+                "gtk4",
+                "mesa",
+                "xorg-xwayland",
+            ],
+            SessionPreset::Plasma => &[
+                "@plasma",
+                "qt6-base",
+                "qt6-wayland",
+                "qt6-declarative",
+                "mesa",
+                "xorg-xwayland",
+            ],
+            SessionPreset::Sway => &["sway", "swaybg", "wlroots*", "mesa", "xorg-xwayland"],
+            SessionPreset::Hyprland => &[
+                "hyprland",
+                "aquamarine",
+                "hyprutils",
+                "hyprlang",
+                "xdg-desktop-portal-hyprland",
+                "mesa",
+                "xorg-xwayland",
+            ],
+            SessionPreset::Xfce => &["@xfce4", "gtk3", "mesa", "xorg-server"],
+            SessionPreset::I3 => &["i3-wm", "mesa", "xorg-server"],
+            SessionPreset::Auto => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            SessionPreset::detect(Some("ubuntu:GNOME"), None),
+            Some(SessionPreset::Gnome)
+        );
+        assert_eq!(
+            SessionPreset::detect(Some("KDE"), Some("KDE")),
+            Some(SessionPreset::Plasma)
+        );
+        assert_eq!(
+            SessionPreset::detect(None, Some("hyprland")),
+            Some(SessionPreset::Hyprland)
+        );
+        assert_eq!(
+            SessionPreset::detect(Some("Unknown"), Some("sway")),
+            Some(SessionPreset::Sway)
+        );
+        assert_eq!(SessionPreset::detect(Some("LXQt"), None), None);
+        assert_eq!(SessionPreset::detect(None, None), None);
+    }
+
+    #[test]
+    fn test_resolve_explicit() {
+        assert_eq!(SessionPreset::I3.resolve(), Some(SessionPreset::I3));
+    }
+}
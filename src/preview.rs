@@ -0,0 +1,207 @@
+//! Preview the result of upgrading with the sync databases already on disk.
+//!
+//! Nothing gets downloaded, so the preview is only as recent as the last `pacman -Sy`.
+
+use crate::checks::{Check, CheckReport, CheckResult, TriggeringPackage};
+use crate::package::PackageInfo;
+use crate::package_list::PackageRule;
+use anyhow::{Context, Result, anyhow};
+use log::{info, warn};
+use std::fs;
+use std::path::Path;
+
+pub const PACMAN_CONF_PATH: &str = "/etc/pacman.conf";
+
+/// An installed package with a newer version in the sync databases.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PendingUpgrade {
+    pub name: String,
+    pub installed_version: String,
+    pub new_version: String,
+}
+
+/// Register the sync databases of the repositories configured in `pacman_conf`.
+///
+/// They get registered in the order of the config like pacman does, since the first repository
+/// with a package wins. Databases of removed repositories may still be on disk and are skipped.
+/// The signatures are not checked, since the databases are only read.
+pub fn register_sync_dbs(alpm: &alpm::Alpm, pacman_conf: &Path) -> Result<()> {
+    let config = fs::read_to_string(pacman_conf)
+        .with_context(|| anyhow!("Could not read {}", pacman_conf.display()))?;
+    for name in repositories(&config) {
+        info!("Registering sync database {name}");
+        if let Err(err) = alpm.register_syncdb(name, alpm::SigLevel::NONE) {
+            warn!("Could not register sync database {name}: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// The repository sections of a pacman config, in order.
+fn repositories(config: &str) -> Vec<&str> {
+    config
+        .lines()
+        .filter_map(|line| {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            line.strip_prefix('[')?.strip_suffix(']')
+        })
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != "options")
+        .collect()
+}
+
+/// Find the installed packages with newer versions in the registered sync databases.
+pub fn pending_upgrades(alpm: &alpm::Alpm) -> Vec<PendingUpgrade> {
+    alpm.localdb()
+        .pkgs()
+        .iter()
+        .filter_map(|package| {
+            let new_package = package.sync_new_version(alpm.syncdbs())?;
+            Some(PendingUpgrade {
+                name: package.name().to_owned(),
+                installed_version: package.version().to_string(),
+                new_version: new_package.version().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Applies the rules of `KernelChecker` and `CriticalPackagesCheck` to pending upgrades.
+pub struct PreviewCheck {
+    pub upgrades: Vec<PendingUpgrade>,
+    /// The package of the running kernel, if it could be detected.
+    pub kernel_package: Option<String>,
+    pub reboot_packages: Vec<PackageRule>,
+    pub restart_session_packages: Vec<PackageRule>,
+}
+
+impl PreviewCheck {
+    fn result_for(&self, upgrade: &PendingUpgrade) -> CheckResult {
+        let triggers = |rules: &[PackageRule]| {
+            rules.iter().any(|rule| {
+                rule.name == upgrade.name
+                    && rule
+                        .min_change
+                        .triggered_by(Some(&upgrade.installed_version), &upgrade.new_version)
+            })
+        };
+        if self.kernel_package.as_deref() == Some(upgrade.name.as_str()) {
+            CheckResult::KernelUpdate
+        } else if triggers(&self.reboot_packages) {
+            CheckResult::Reboot
+        } else if triggers(&self.restart_session_packages) {
+            CheckResult::RestartSession
+        } else {
+            CheckResult::Nothing
+        }
+    }
+}
+
+impl Check for PreviewCheck {
+    fn check(&self) -> CheckReport {
+        let mut report = CheckReport::default();
+        for upgrade in &self.upgrades {
+            let result = self.result_for(upgrade);
+            if result > CheckResult::Nothing {
+                report.add_package(TriggeringPackage {
+                    name: upgrade.name.clone(),
+                    info: PackageInfo {
+                        version: upgrade.new_version.clone(),
+                        install_date: None,
+                    },
+                    previous_version: Some(upgrade.installed_version.clone()),
+                    result,
+                });
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::version::ChangeLevel;
+
+    fn upgrade(name: &str) -> PendingUpgrade {
+        PendingUpgrade {
+            name: name.to_owned(),
+            installed_version: "1.0-1".to_owned(),
+            new_version: "1.1-1".to_owned(),
+        }
+    }
+
+    fn rule(name: &str, min_change: ChangeLevel) -> PackageRule {
+        PackageRule {
+            name: name.to_owned(),
+            min_change,
+        }
+    }
+
+    fn preview_check(upgrades: Vec<PendingUpgrade>) -> PreviewCheck {
+        PreviewCheck {
+            upgrades,
+            kernel_package: Some("linux".to_owned()),
+            reboot_packages: vec![rule("systemd", ChangeLevel::Pkgrel)],
+            restart_session_packages: vec![
+                rule("xorg-server", ChangeLevel::Pkgrel),
+                rule("mesa", ChangeLevel::Minor),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_repositories() {
+        let config = "\
+[options]
+HoldPkg = pacman glibc
+#[core-testing]
+#Include = /etc/pacman.d/mirrorlist
+
+[core]
+Include = /etc/pacman.d/mirrorlist
+
+[extra] # the rest
+Include = /etc/pacman.d/mirrorlist
+
+[custom]
+Server = file:///home/custompkgs
+";
+        assert_eq!(repositories(config), vec!["core", "extra", "custom"]);
+    }
+
+    #[test]
+    fn test_preview_nothing() {
+        let report = preview_check(vec![upgrade("firefox")]).check();
+        assert_eq!(report.result, CheckResult::Nothing);
+        assert!(report.packages.is_empty());
+    }
+
+    #[test]
+    fn test_preview_rules() {
+        let report = preview_check(vec![upgrade("xorg-server"), upgrade("systemd")]).check();
+        assert_eq!(report.result, CheckResult::Reboot);
+        assert_eq!(report.packages.len(), 2);
+        assert_eq!(
+            report.packages[1].previous_version.as_deref(),
+            Some("1.0-1")
+        );
+        assert_eq!(report.packages[1].info.version, "1.1-1");
+
+        let report = preview_check(vec![upgrade("linux")]).check();
+        assert_eq!(report.result, CheckResult::KernelUpdate);
+    }
+
+    #[test]
+    fn test_preview_change_level() {
+        let mut mesa = upgrade("mesa");
+        mesa.new_version = "1.0-2".to_owned();
+        assert_eq!(
+            preview_check(vec![mesa]).check().result,
+            CheckResult::Nothing
+        );
+        // 1.0 → 1.1 is a minor change.
+        let report = preview_check(vec![upgrade("mesa")]).check();
+        assert_eq!(report.result, CheckResult::RestartSession);
+    }
+}
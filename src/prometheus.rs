@@ -0,0 +1,166 @@
+//! Metrics for the textfile collector of the Prometheus node exporter.
+
+use crate::checks::{CheckReport, CheckResult};
+use anyhow::{Context, Result, anyhow};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// The reasons for `reboot_required` and the results they stand for.
+const REASONS: [(&str, CheckResult); 3] = [
+    ("kernel", CheckResult::KernelUpdate),
+    ("packages", CheckResult::Reboot),
+    ("session", CheckResult::RestartSession),
+];
+
+/// Write the metrics for `report` to `path`.
+///
+/// `pending_since` is the result of `CheckReport::reboot_pending_since`. The file gets replaced
+/// atomically, so the node exporter never reads a partial file.
+pub fn write_textfile(
+    path: &Path,
+    report: &CheckReport,
+    now: i64,
+    pending_since: Option<i64>,
+) -> Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    // The node exporter only reads files ending in ".prom".
+    tmp_path.push(".tmp");
+    fs::write(&tmp_path, metrics(report, now, pending_since))
+        .with_context(|| anyhow!("Could not write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path).with_context(|| anyhow!("Could not replace {}", path.display()))
+}
+
+fn metrics(report: &CheckReport, now: i64, pending_since: Option<i64>) -> String {
+    let mut output = String::new();
+    header(
+        &mut output,
+        "reboot_required",
+        "Whether a reboot or session restart is required, by reason.",
+    );
+    for (reason, result) in REASONS {
+        let required = report
+            .packages
+            .iter()
+            .any(|package| package.result == result);
+        writeln!(
+            output,
+            "reboot_required{{reason=\"{reason}\"}} {}",
+            u8::from(required)
+        )
+        .ok();
+    }
+
+    header(
+        &mut output,
+        "package_installed_after_boot",
+        "Relevant packages which got upgraded since boot.",
+    );
+    for package in &report.packages {
+        writeln!(
+            output,
+            "package_installed_after_boot{{package=\"{}\",version=\"{}\"}} 1",
+            escape(&package.name),
+            escape(&package.info.version)
+        )
+        .ok();
+    }
+
+    if let Some(kernel) = &report.kernel {
+        header(
+            &mut output,
+            "kernel_info",
+            "The running and the installed kernel version.",
+        );
+        writeln!(
+            output,
+            "kernel_info{{running=\"{}\",installed=\"{}\"}} 1",
+            escape(&kernel.running),
+            escape(&kernel.installed.version)
+        )
+        .ok();
+    }
+
+    header(
+        &mut output,
+        "reboot_pending_seconds",
+        "Seconds since a reboot first became required, 0 if none is.",
+    );
+    let pending = pending_since.map_or(0, |since| (now - since).max(0));
+    writeln!(output, "reboot_pending_seconds {pending}").ok();
+    output
+}
+
+fn header(output: &mut String, name: &str, help: &str) {
+    writeln!(output, "# HELP {name} {help}").ok();
+    writeln!(output, "# TYPE {name} gauge").ok();
+}
+
+/// Escape a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::{KernelVersions, triggering_package};
+    use crate::package::PackageInfo;
+    use crate::test_dir::TestDir;
+
+    fn report() -> CheckReport {
+        let mut report = CheckReport {
+            kernel: Some(KernelVersions {
+                running: "6.10.5.arch1.1".to_owned(),
+                installed: PackageInfo {
+                    version: "6.10.6.arch1.1".to_owned(),
+                    install_date: Some(1000),
+                },
+            }),
+            ..CheckReport::default()
+        };
+        let mut linux = triggering_package("linux", CheckResult::KernelUpdate);
+        linux.info.version = "6.10.6.arch1-1".to_owned();
+        linux.info.install_date = Some(1000);
+        report.add_package(linux);
+        report
+    }
+
+    #[test]
+    fn test_metrics() {
+        assert_eq!(
+            metrics(&report(), 1100, Some(1000)),
+            "# HELP reboot_required Whether a reboot or session restart is required, by reason.\n\
+             # TYPE reboot_required gauge\n\
+             reboot_required{reason=\"kernel\"} 1\n\
+             reboot_required{reason=\"packages\"} 0\n\
+             reboot_required{reason=\"session\"} 0\n\
+             # HELP package_installed_after_boot Relevant packages which got upgraded since boot.\n\
+             # TYPE package_installed_after_boot gauge\n\
+             package_installed_after_boot{package=\"linux\",version=\"6.10.6.arch1-1\"} 1\n\
+             # HELP kernel_info The running and the installed kernel version.\n\
+             # TYPE kernel_info gauge\n\
+             kernel_info{running=\"6.10.5.arch1.1\",installed=\"6.10.6.arch1.1\"} 1\n\
+             # HELP reboot_pending_seconds Seconds since a reboot first became required, 0 if none is.\n\
+             # TYPE reboot_pending_seconds gauge\n\
+             reboot_pending_seconds 100\n"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_write_textfile() {
+        let dir = TestDir::new("prometheus");
+        let path = dir.path().join("reboot-arch-btw.prom");
+        write_textfile(&path, &CheckReport::default(), 0, None).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("reboot_pending_seconds 0\n"));
+    }
+}
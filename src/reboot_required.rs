@@ -0,0 +1,118 @@
+//! Debian compatible `/run/reboot-required` flag files.
+//!
+//! `reboot-required` flags that a reboot is needed and `reboot-required.pkgs` lists the packages
+//! which caused it, one per line. Many unattended reboot scripts and MOTD snippets look for them.
+
+use crate::checks::{CheckReport, CheckResult};
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+pub const RUN_DIR: &str = "/run";
+const FLAG_FILE: &str = "reboot-required";
+const PACKAGES_FILE: &str = "reboot-required.pkgs";
+const FLAG_CONTENT: &str = "*** System restart required ***\n";
+
+/// Write the files to `dir` if `report` requires a reboot and remove them otherwise.
+///
+/// With `keep_existing` already listed packages are kept and the files never get removed. This is
+/// used when only some packages got checked, e.g. the targets of a pacman hook.
+pub fn update(dir: &Path, report: &CheckReport, keep_existing: bool) -> Result<()> {
+    let flag_path = dir.join(FLAG_FILE);
+    let packages_path = dir.join(PACKAGES_FILE);
+    if report.result < CheckResult::Reboot {
+        if !keep_existing {
+            remove(&flag_path)?;
+            remove(&packages_path)?;
+        }
+        return Ok(());
+    }
+
+    let mut packages = vec![];
+    if keep_existing {
+        match fs::read_to_string(&packages_path) {
+            Ok(content) => packages.extend(content.lines().map(str::to_owned)),
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| anyhow!("Could not read {}", packages_path.display()));
+            }
+        }
+    }
+    for package in &report.packages {
+        if package.result >= CheckResult::Reboot && !packages.contains(&package.name) {
+            packages.push(package.name.clone());
+        }
+    }
+    let packages_content: String = packages.iter().map(|name| format!("{name}\n")).collect();
+
+    fs::write(&flag_path, FLAG_CONTENT)
+        .with_context(|| anyhow!("Could not write {}", flag_path.display()))?;
+    fs::write(&packages_path, packages_content)
+        .with_context(|| anyhow!("Could not write {}", packages_path.display()))
+}
+
+fn remove(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            Err(err).with_context(|| anyhow!("Could not remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::triggering_package;
+    use crate::test_dir::TestDir;
+
+    fn report(packages: &[(&str, CheckResult)]) -> CheckReport {
+        let mut report = CheckReport::default();
+        for (name, result) in packages {
+            report.add_package(triggering_package(name, *result));
+        }
+        report
+    }
+
+    fn read(dir: &Path, file: &str) -> Option<String> {
+        fs::read_to_string(dir.join(file)).ok()
+    }
+
+    #[test]
+    fn test_update() {
+        let test_dir = TestDir::new("reboot-required");
+        let dir = test_dir.path();
+
+        let reboot = report(&[
+            ("linux", CheckResult::KernelUpdate),
+            ("xorg-server", CheckResult::RestartSession),
+            ("systemd", CheckResult::Reboot),
+        ]);
+        update(dir, &reboot, false).unwrap();
+        assert_eq!(read(dir, FLAG_FILE).as_deref(), Some(FLAG_CONTENT));
+        assert_eq!(
+            read(dir, PACKAGES_FILE).as_deref(),
+            Some("linux\nsystemd\n")
+        );
+
+        // Hooks only see some packages, so they add to the list.
+        update(dir, &report(&[("amd-ucode", CheckResult::Reboot)]), true).unwrap();
+        assert_eq!(
+            read(dir, PACKAGES_FILE).as_deref(),
+            Some("linux\nsystemd\namd-ucode\n")
+        );
+        update(dir, &CheckReport::default(), true).unwrap();
+        assert!(read(dir, FLAG_FILE).is_some());
+
+        update(
+            dir,
+            &report(&[("xorg-server", CheckResult::RestartSession)]),
+            false,
+        )
+        .unwrap();
+        assert_eq!(read(dir, FLAG_FILE), None);
+        assert_eq!(read(dir, PACKAGES_FILE), None);
+    }
+}
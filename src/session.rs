@@ -1,11 +1,51 @@
 use anyhow::{Context, Result, anyhow};
+use std::env;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::time::Duration;
 use time::OffsetDateTime;
 use utmp_rs::UtmpEntry;
 
+/// Time since boot, read from `/proc/uptime`.
+pub fn uptime() -> Result<Duration> {
+    let content =
+        fs::read_to_string("/proc/uptime").with_context(|| anyhow!("Could not read uptime"))?;
+    parse_uptime(&content)
+}
+
+fn parse_uptime(content: &str) -> Result<Duration> {
+    let secs: f64 = content
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("Empty uptime"))?
+        .parse()
+        .with_context(|| anyhow!("Could not parse uptime"))?;
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// Random ID of the current boot, read from `/proc/sys/kernel/random/boot_id`.
+///
+/// It changes with every boot, so it tells whether the system got rebooted in between.
+pub fn boot_id() -> Result<String> {
+    let content = fs::read_to_string("/proc/sys/kernel/random/boot_id")
+        .with_context(|| anyhow!("Could not read boot ID"))?;
+    Ok(content.trim().to_owned())
+}
+
+/// Whether we run as a regular user in a login session or in the user's service manager.
+///
+/// Neither is the case for pacman hooks, which run as root, or via sudo with a reset environment.
+pub fn in_user_session() -> bool {
+    let is_root = fs::metadata("/proc/self").is_ok_and(|metadata| metadata.uid() == 0);
+    !is_root
+        && (env::var_os("XDG_SESSION_ID").is_some() || env::var_os("XDG_RUNTIME_DIR").is_some())
+}
+
 #[derive(Clone, Copy)]
 pub struct SessionInfo {
     pub boot_time: OffsetDateTime,
-    pub session_time: OffsetDateTime,
+    /// `None` if nobody is logged in, e.g. on a headless server.
+    pub session_time: Option<OffsetDateTime>,
 }
 
 impl SessionInfo {
@@ -35,7 +75,7 @@ impl SessionInfo {
         Ok(SessionInfo {
             // TODO: Should we make this stuff optional and just print warnings?
             boot_time: *boot_time.ok_or_else(|| anyhow!("No boot time available"))?,
-            session_time: *session_time.ok_or_else(|| anyhow!("No session time available"))?,
+            session_time: session_time.copied(),
         })
     }
 }
@@ -44,6 +84,16 @@ impl SessionInfo {
 mod test {
     use super::*;
     use time::Duration;
+
+    #[test]
+    fn test_parse_uptime() {
+        assert_eq!(
+            parse_uptime("350735.47 234388.90\n").unwrap(),
+            std::time::Duration::from_millis(350735470)
+        );
+        assert!(parse_uptime("").is_err());
+    }
+
     #[test]
     fn test_from_utmp_entries() {
         let now = OffsetDateTime::now_utc();
@@ -65,6 +115,20 @@ mod test {
         assert_eq!(
             session_info.boot_time,
             now.checked_sub(Duration::HOUR).unwrap()
-        )
+        );
+        assert_eq!(session_info.session_time, Some(now));
+    }
+
+    #[test]
+    fn test_from_utmp_entries_without_session() {
+        let now = OffsetDateTime::now_utc();
+        let utmp_entries = [UtmpEntry::BootTime {
+            kernel_version: "5.19.9-arch1-1".to_owned(),
+            time: now,
+        }];
+        let session_info = SessionInfo::from_utmp_entries(&utmp_entries).unwrap();
+        assert_eq!(session_info.boot_time, now);
+        assert_eq!(session_info.session_time, None);
+        assert!(SessionInfo::from_utmp_entries(&[]).is_err());
     }
 }
@@ -0,0 +1,149 @@
+use crate::checks::{CheckReport, CheckResult};
+use crate::config::xdg_dir;
+use crate::notification::ShownNotification;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// What we remember between runs.
+#[derive(PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// The report of the last run.
+    pub last_report: Option<CheckReport>,
+    /// Whether the user acknowledged `last_report`.
+    pub acknowledged: bool,
+    /// Unix timestamp until which notifications for `last_report` are snoozed.
+    pub snoozed_until: Option<i64>,
+    /// The notification shown by the last run, if it may still be on screen.
+    pub notification: Option<ShownNotification>,
+    /// The last result logged to the journal with `--journal`.
+    pub logged_result: Option<CheckResult>,
+}
+
+/// Directory for our persistent state, usually `~/.local/state/reboot-arch-btw`.
+pub fn state_dir() -> Result<PathBuf> {
+    Ok(xdg_dir("XDG_STATE_HOME", ".local/state")?.join("reboot-arch-btw"))
+}
+
+impl State {
+    pub fn path() -> Result<PathBuf> {
+        Ok(state_dir()?.join("state.json"))
+    }
+
+    pub fn load() -> Result<State> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| anyhow!("Could not parse {}", path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(State::default()),
+            Err(err) => Err(err).with_context(|| anyhow!("Could not read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        let dir = path.parent().expect("state file has a parent directory");
+        fs::create_dir_all(dir).with_context(|| anyhow!("Could not create {}", dir.display()))?;
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| anyhow!("Could not write {}", path.display()))
+    }
+
+    /// Remember the current report.
+    ///
+    /// Acknowledgement and snooze only apply to the report they were given for, so they get reset
+    /// as soon as something new shows up.
+    pub fn update(&mut self, report: &CheckReport) {
+        if self.last_report.as_ref() != Some(report) {
+            *self = State {
+                last_report: Some(report.clone()),
+                notification: self.notification.take(),
+                logged_result: self.logged_result,
+                ..State::default()
+            };
+        }
+    }
+
+    pub fn acknowledge(&mut self) {
+        self.acknowledged = true;
+    }
+
+    pub fn snooze_until(&mut self, timestamp: i64) {
+        self.snoozed_until = Some(timestamp);
+    }
+
+    pub fn is_snoozed(&self, now: i64) -> bool {
+        self.snoozed_until.is_some_and(|until| until > now)
+    }
+
+    /// Whether notifications for the last report should be suppressed at time `now`.
+    pub fn is_suppressed(&self, now: i64) -> bool {
+        self.acknowledged || self.is_snoozed(now)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::triggering_package;
+
+    fn report(version: &str) -> CheckReport {
+        let mut systemd = triggering_package("systemd", CheckResult::Reboot);
+        systemd.info.version = version.to_owned();
+        systemd.info.install_date = Some(1000);
+        let mut report = CheckReport::default();
+        report.add_package(systemd);
+        report
+    }
+
+    #[test]
+    fn test_acknowledged_until_something_changes() {
+        let mut state = State::default();
+        state.update(&report("256.4-1"));
+        state.acknowledge();
+        assert!(state.is_suppressed(0));
+
+        state.update(&report("256.4-1"));
+        assert!(state.is_suppressed(0));
+
+        state.update(&report("256.5-1"));
+        assert!(!state.is_suppressed(0));
+    }
+
+    #[test]
+    fn test_update_keeps_notification() {
+        let notification = ShownNotification {
+            id: 42,
+            server: ":1.23".to_owned(),
+        };
+        let mut state = State::default();
+        state.update(&report("256.4-1"));
+        state.notification = Some(notification.clone());
+
+        state.logged_result = Some(CheckResult::Reboot);
+
+        state.update(&report("256.5-1"));
+        assert_eq!(state.notification, Some(notification));
+        assert_eq!(state.logged_result, Some(CheckResult::Reboot));
+    }
+
+    #[test]
+    fn test_snooze_expires() {
+        let mut state = State::default();
+        state.update(&report("256.4-1"));
+        state.snooze_until(100);
+        assert!(state.is_suppressed(99));
+        assert!(!state.is_suppressed(100));
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let mut state = State::default();
+        state.update(&report("256.4-1"));
+        state.acknowledge();
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<State>(&json).unwrap(), state);
+    }
+}
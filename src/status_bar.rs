@@ -0,0 +1,188 @@
+//! Output for status bars like Waybar, i3blocks and polybar.
+
+use crate::checks::{CheckReport, CheckResult, TriggeringPackage};
+use crate::i18n::{tr, tr_args};
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// i3blocks marks the block as urgent when the command exits with this code.
+const I3BLOCKS_URGENT_EXIT_CODE: i32 = 33;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// JSON for a custom Waybar module with "return-type": "json".
+    Waybar,
+    /// Full text, short text and color lines. Kernel updates exit with the urgent code.
+    I3blocks,
+    /// A single line with color formatting tags.
+    Polybar,
+}
+
+/// What to show in the status bar.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Status {
+    pub text: String,
+    pub tooltip: String,
+    /// CSS class for Waybar.
+    pub class: &'static str,
+    /// Severity from 0 to 100, Waybar can pick icons by it.
+    pub percentage: u8,
+    #[serde(skip)]
+    pub color: Option<&'static str>,
+    #[serde(skip)]
+    pub urgent: bool,
+}
+
+impl Status {
+    pub fn for_report(report: &CheckReport) -> Status {
+        let (class, percentage, color) = match report.result {
+            CheckResult::Nothing => ("nothing", 0, None),
+            CheckResult::RestartSession => ("restart-session", 33, Some("#FFFF00")),
+            CheckResult::Reboot => ("reboot", 66, Some("#FFA500")),
+            CheckResult::KernelUpdate => ("kernel-update", 100, Some("#FF0000")),
+        };
+        Status {
+            text: report.result.summary(),
+            tooltip: tooltip(report),
+            class,
+            percentage,
+            color,
+            urgent: report.result == CheckResult::KernelUpdate,
+        }
+    }
+
+    pub fn transaction_in_progress() -> Status {
+        Status {
+            text: tr("status-transaction-in-progress"),
+            tooltip: tr("transaction-in-progress"),
+            class: "transaction-in-progress",
+            percentage: 0,
+            color: None,
+            urgent: false,
+        }
+    }
+}
+
+/// The kernel versions like with `--verbose`, followed by the updated packages.
+fn tooltip(report: &CheckReport) -> String {
+    let mut lines = vec![];
+    if let Some(kernel) = &report.kernel {
+        lines.push(tr("verbose-kernel"));
+        lines.push(format!(
+            " {}",
+            tr_args(
+                "verbose-kernel-installed",
+                &[
+                    ("version", kernel.installed.version.as_str().into()),
+                    ("ago", kernel.installed.installed_reltime().into()),
+                ],
+            )
+        ));
+        lines.push(format!(
+            " {}",
+            tr_args(
+                "verbose-kernel-running",
+                &[("version", kernel.running.as_str().into())],
+            )
+        ));
+    }
+    lines.extend(
+        report
+            .packages
+            .iter()
+            .filter(|package| package.result != CheckResult::KernelUpdate)
+            .map(TriggeringPackage::describe),
+    );
+    lines.push(report.result.body());
+    lines.join("\n")
+}
+
+impl Format {
+    pub fn render(&self, status: &Status) -> String {
+        match self {
+            Format::Waybar => serde_json::to_string(status).expect("status is serializable"),
+            Format::I3blocks => {
+                let mut output = format!("{}\n{}\n", status.text, status.text);
+                if let Some(color) = status.color {
+                    output += &format!("{color}\n");
+                }
+                output.trim_end().to_owned()
+            }
+            Format::Polybar => {
+                let text = status.text.replace('%', "%%");
+                match status.color {
+                    Some(color) => format!("%{{F{color}}}{text}%{{F-}}"),
+                    None => text,
+                }
+            }
+        }
+    }
+
+    /// The exit code to use after printing the output.
+    pub fn exit_code(&self, status: &Status) -> i32 {
+        match self {
+            Format::I3blocks if status.urgent => I3BLOCKS_URGENT_EXIT_CODE,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::checks::triggering_package;
+
+    fn report() -> CheckReport {
+        let mut systemd = triggering_package("systemd", CheckResult::Reboot);
+        systemd.info.version = "256.5-1".to_owned();
+        systemd.previous_version = Some("256.4-1".to_owned());
+        let mut report = CheckReport::default();
+        report.add_package(systemd);
+        report
+    }
+
+    #[test]
+    fn test_waybar() {
+        let status = Status::for_report(&report());
+        assert_eq!(
+            Format::Waybar.render(&status),
+            "{\"text\":\"Reboot arch btw\",\
+             \"tooltip\":\"systemd 256.4-1 → 256.5-1 (unknown)\\nSystem packages got updated. \
+             You should reboot your system!\",\
+             \"class\":\"reboot\",\"percentage\":66}"
+        );
+        assert_eq!(Format::Waybar.exit_code(&status), 0);
+    }
+
+    #[test]
+    fn test_i3blocks() {
+        let status = Status::for_report(&report());
+        assert_eq!(
+            Format::I3blocks.render(&status),
+            "Reboot arch btw\nReboot arch btw\n#FFA500"
+        );
+        assert_eq!(
+            Format::I3blocks.render(&Status::for_report(&CheckReport::default())),
+            "All good\nAll good"
+        );
+
+        let kernel_update = CheckReport {
+            result: CheckResult::KernelUpdate,
+            ..CheckReport::default()
+        };
+        let status = Status::for_report(&kernel_update);
+        assert_eq!(Format::I3blocks.exit_code(&status), 33);
+    }
+
+    #[test]
+    fn test_polybar() {
+        assert_eq!(
+            Format::Polybar.render(&Status::for_report(&report())),
+            "%{F#FFA500}Reboot arch btw%{F-}"
+        );
+        assert_eq!(
+            Format::Polybar.render(&Status::for_report(&CheckReport::default())),
+            "All good"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Messages built from user defined templates.
+//!
+//! Templates may contain the following placeholders:
+//!
+//! * `{packages}`: comma separated list of the updated packages, e.g.
+//!   "systemd 256.4-1 → 256.5-1 (2 hours ago)"
+//! * `{details}`: the default description of the findings, one per line
+//! * `{running_kernel}` and `{installed_kernel}`: the kernel versions
+//! * `{uptime}`: time since boot, e.g. "3 days"
+//! * `{user}`: the name of the user
+
+use crate::checks::{CheckReport, CheckResult, TriggeringPackage};
+use crate::config::MessageTemplates;
+use crate::duration::format_duration;
+use crate::i18n::tr;
+use crate::session;
+use log::warn;
+use std::env;
+
+/// Summary and body of the message for a report.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Message {
+    pub summary: String,
+    pub body: String,
+}
+
+impl Message {
+    /// Build the message for `report`, using the templates if there are any for its result.
+    pub fn for_report(templates: &MessageTemplates, report: &CheckReport) -> Message {
+        let template = templates.for_result(&report.result);
+        let summary_template = template.and_then(|template| template.summary.as_deref());
+        let body_template = template.and_then(|template| template.body.as_deref());
+        if summary_template.is_none() && body_template.is_none() {
+            return Self::default_for(report);
+        }
+
+        let values = placeholder_values(report);
+        Message {
+            summary: summary_template.map_or_else(
+                || report.result.summary(),
+                |template| render(template, &values),
+            ),
+            body: body_template.map_or_else(|| report.body(), |template| render(template, &values)),
+        }
+    }
+
+    pub fn default_for(report: &CheckReport) -> Message {
+        Message {
+            summary: report.result.summary(),
+            body: report.body(),
+        }
+    }
+}
+
+fn placeholder_values(report: &CheckReport) -> Vec<(&'static str, String)> {
+    let packages = report
+        .packages
+        .iter()
+        .filter(|package| package.result != CheckResult::KernelUpdate)
+        .map(TriggeringPackage::describe)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let (running_kernel, installed_kernel) = match &report.kernel {
+        Some(kernel) => (kernel.running.clone(), kernel.installed.version.clone()),
+        None => (tr("unknown"), tr("unknown")),
+    };
+    let uptime = session::uptime().map_or_else(
+        |err| {
+            warn!("{err:#}");
+            tr("unknown")
+        },
+        |uptime| format_duration(uptime.as_secs()),
+    );
+    let user = env::var("USER").unwrap_or_default();
+    vec![
+        ("packages", packages),
+        ("details", report.details().join("\n")),
+        ("running_kernel", running_kernel),
+        ("installed_kernel", installed_kernel),
+        ("uptime", uptime),
+        ("user", user),
+    ]
+}
+
+/// Replace `{name}` placeholders in `template`. Unknown placeholders are kept as they are.
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut remaining = template;
+    while let Some(start) = remaining.find('{') {
+        output.push_str(&remaining[..start]);
+        let placeholder = &remaining[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            let name = &placeholder[1..end];
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                output.push_str(value);
+                remaining = &placeholder[end + 1..];
+            }
+            None => {
+                output.push('{');
+                remaining = &placeholder[1..];
+            }
+        }
+    }
+    output.push_str(remaining);
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::MessageTemplate;
+
+    #[test]
+    fn test_render() {
+        let values = [
+            ("user", "alice".to_owned()),
+            ("uptime", "3 days".to_owned()),
+        ];
+        assert_eq!(
+            render("{user}: up for {uptime}, please reboot", &values),
+            "alice: up for 3 days, please reboot"
+        );
+    }
+
+    #[test]
+    fn test_render_keeps_unknown_placeholders() {
+        let values = [("user", "alice".to_owned())];
+        assert_eq!(render("{foo} {user} {", &values), "{foo} alice {");
+    }
+
+    #[test]
+    fn test_message_without_templates_is_default() {
+        let report = CheckReport {
+            result: CheckResult::Reboot,
+            ..CheckReport::default()
+        };
+        let message = Message::for_report(&MessageTemplates::default(), &report);
+        assert_eq!(message, Message::default_for(&report));
+        assert_eq!(message.summary, "Reboot arch btw");
+    }
+
+    #[test]
+    fn test_message_with_summary_template() {
+        let report = CheckReport {
+            result: CheckResult::Reboot,
+            ..CheckReport::default()
+        };
+        let templates = MessageTemplates {
+            reboot: MessageTemplate {
+                summary: Some("IT: please reboot before Friday".to_owned()),
+                body: None,
+            },
+            ..MessageTemplates::default()
+        };
+        let message = Message::for_report(&templates, &report);
+        assert_eq!(message.summary, "IT: please reboot before Friday");
+        assert_eq!(message.body, report.body());
+    }
+}
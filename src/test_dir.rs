@@ -0,0 +1,32 @@
+//! Temporary directories for tests which touch the file system.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory of its own for one test, removed again when dropped.
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    /// Create the directory for the test `name`, which has to be unique across all tests.
+    ///
+    /// The process ID keeps concurrent test runs apart.
+    pub fn new(name: &str) -> TestDir {
+        let path = env::temp_dir().join(format!(
+            "reboot-arch-btw-test-{name}-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&path).expect("can create test directory");
+        TestDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
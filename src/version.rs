@@ -0,0 +1,122 @@
+//! How much a package version changed, to ignore e.g. rebuilds which only bump the pkgrel.
+//!
+//! Versions look like `epoch:pkgver-pkgrel`, where the epoch is optional and the first two parts
+//! of the pkgver are taken as major and minor version.
+
+use anyhow::{Result, anyhow};
+use std::str::FromStr;
+
+/// The most significant part of a version which changed, least significant first.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Default, Clone, Copy)]
+pub enum ChangeLevel {
+    /// Any change, including rebuilds and reinstalls.
+    #[default]
+    Pkgrel,
+    Pkgver,
+    Minor,
+    Major,
+    Epoch,
+}
+
+impl FromStr for ChangeLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(level: &str) -> Result<ChangeLevel> {
+        match level {
+            "pkgrel" => Ok(ChangeLevel::Pkgrel),
+            "pkgver" => Ok(ChangeLevel::Pkgver),
+            "minor" => Ok(ChangeLevel::Minor),
+            "major" => Ok(ChangeLevel::Major),
+            "epoch" => Ok(ChangeLevel::Epoch),
+            _ => Err(anyhow!(
+                "Unknown change level {level}, expected epoch, major, minor, pkgver or pkgrel"
+            )),
+        }
+    }
+}
+
+impl ChangeLevel {
+    /// Whether an upgrade from `previous` to `installed` reaches this level.
+    ///
+    /// If the previous version is unknown, it has to be assumed that it does.
+    pub fn triggered_by(self, previous: Option<&str>, installed: &str) -> bool {
+        match previous {
+            _ if self == ChangeLevel::Pkgrel => true,
+            Some(previous) => change_level(previous, installed).is_some_and(|level| level >= self),
+            None => true,
+        }
+    }
+}
+
+/// Split a version into epoch, pkgver and pkgrel.
+fn split(version: &str) -> (&str, &str, &str) {
+    let (epoch, rest) = version.split_once(':').unwrap_or(("0", version));
+    let (pkgver, pkgrel) = rest.rsplit_once('-').unwrap_or((rest, ""));
+    (epoch, pkgver, pkgrel)
+}
+
+/// The most significant part which differs between `old` and `new`, `None` if they are equal.
+pub fn change_level(old: &str, new: &str) -> Option<ChangeLevel> {
+    let (old_epoch, old_pkgver, old_pkgrel) = split(old);
+    let (new_epoch, new_pkgver, new_pkgrel) = split(new);
+    let mut old_parts = old_pkgver.split('.');
+    let mut new_parts = new_pkgver.split('.');
+    if old_epoch != new_epoch {
+        Some(ChangeLevel::Epoch)
+    } else if old_parts.next() != new_parts.next() {
+        Some(ChangeLevel::Major)
+    } else if old_parts.next() != new_parts.next() {
+        Some(ChangeLevel::Minor)
+    } else if old_pkgver != new_pkgver {
+        Some(ChangeLevel::Pkgver)
+    } else if old_pkgrel != new_pkgrel {
+        Some(ChangeLevel::Pkgrel)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_change_level() {
+        assert_eq!(change_level("256.5-1", "256.5-1"), None);
+        assert_eq!(
+            change_level("256.5-1", "256.5-2"),
+            Some(ChangeLevel::Pkgrel)
+        );
+        assert_eq!(
+            change_level("1.2.3-1", "1.2.4-1"),
+            Some(ChangeLevel::Pkgver)
+        );
+        assert_eq!(change_level("256.4-1", "256.5-1"), Some(ChangeLevel::Minor));
+        assert_eq!(change_level("256-1", "256.1-1"), Some(ChangeLevel::Minor));
+        assert_eq!(change_level("255.9-1", "256.0-1"), Some(ChangeLevel::Major));
+        assert_eq!(
+            change_level("24.2.1-1", "1:24.2.1-1"),
+            Some(ChangeLevel::Epoch)
+        );
+        assert_eq!(
+            change_level("1:24.2.1-1", "1:24.2.2-1"),
+            Some(ChangeLevel::Pkgver)
+        );
+    }
+
+    #[test]
+    fn test_parse_change_level() {
+        assert_eq!("minor".parse::<ChangeLevel>().unwrap(), ChangeLevel::Minor);
+        assert!("patch".parse::<ChangeLevel>().is_err());
+    }
+
+    #[test]
+    fn test_triggered_by() {
+        // Reinstalls and rebuilds only trigger the default level.
+        assert!(ChangeLevel::Pkgrel.triggered_by(Some("256.5-1"), "256.5-1"));
+        assert!(!ChangeLevel::Pkgver.triggered_by(Some("256.5-1"), "256.5-2"));
+        assert!(ChangeLevel::Pkgver.triggered_by(Some("256.5-1"), "256.6-1"));
+        assert!(!ChangeLevel::Major.triggered_by(Some("256.5-1"), "256.6-1"));
+        assert!(ChangeLevel::Major.triggered_by(None, "256.6-1"));
+    }
+}
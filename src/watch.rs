@@ -0,0 +1,94 @@
+use crate::db_lock::PACMAN_DB_LOCK_PATH;
+use anyhow::{Context, Result, anyhow};
+use inotify::{Inotify, WatchMask};
+use log::info;
+use std::ffi::OsStr;
+use std::path::Path;
+
+pub const PACMAN_DB_PATH: &str = "/var/lib/pacman";
+pub const PACMAN_LOCAL_DB_PATH: &str = "/var/lib/pacman/local";
+
+/// A change to the pacman database we got notified about.
+#[derive(Debug, PartialEq, Eq)]
+enum Change {
+    /// pacman removed its lock, so a transaction just finished.
+    LockRemoved,
+    /// A package got added to or removed from the local database.
+    LocalDb,
+    Other,
+}
+
+/// Run `check` once and then again after every completed pacman transaction.
+///
+/// Only returns if watching the database fails.
+pub fn watch(mut check: impl FnMut()) -> Result<()> {
+    let mut inotify = Inotify::init().context("Could not initialize inotify")?;
+    let db_watch = inotify
+        .watches()
+        .add(PACMAN_DB_PATH, WatchMask::DELETE)
+        .with_context(|| anyhow!("Could not watch {PACMAN_DB_PATH}"))?;
+    let local_db_watch = inotify
+        .watches()
+        .add(
+            PACMAN_LOCAL_DB_PATH,
+            WatchMask::CREATE | WatchMask::DELETE | WatchMask::MOVED_TO | WatchMask::MOVED_FROM,
+        )
+        .with_context(|| anyhow!("Could not watch {PACMAN_LOCAL_DB_PATH}"))?;
+
+    check();
+
+    let mut buffer = [0; 4096];
+    loop {
+        let events = inotify
+            .read_events_blocking(&mut buffer)
+            .context("Could not read inotify events")?;
+        let changes = events.map(|event| {
+            if event.wd == db_watch && event.name == Some(OsStr::new("db.lck")) {
+                Change::LockRemoved
+            } else if event.wd == local_db_watch {
+                Change::LocalDb
+            } else {
+                Change::Other
+            }
+        });
+        if should_check(changes, Path::new(PACMAN_DB_LOCK_PATH).exists()) {
+            info!("pacman database changed, checking again");
+            check();
+        }
+    }
+}
+
+/// Whether a batch of changes warrants checking again.
+///
+/// While the lock is held the database may be half updated, so changes to the local database only
+/// count if no transaction is running. Otherwise we wait until the lock gets removed.
+fn should_check(changes: impl IntoIterator<Item = Change>, locked: bool) -> bool {
+    changes.into_iter().any(|change| match change {
+        Change::LockRemoved => true,
+        Change::LocalDb => !locked,
+        Change::Other => false,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_after_transaction() {
+        assert!(should_check([Change::LocalDb, Change::LockRemoved], false));
+        assert!(should_check([Change::LockRemoved], true));
+    }
+
+    #[test]
+    fn test_wait_for_running_transaction() {
+        assert!(!should_check([Change::LocalDb, Change::LocalDb], true));
+        assert!(should_check([Change::LocalDb], false));
+    }
+
+    #[test]
+    fn test_ignore_other_changes() {
+        assert!(!should_check([Change::Other], false));
+        assert!(!should_check([], false));
+    }
+}